@@ -300,7 +300,7 @@ fn test_expr() {
     let mut vars: HashMap<String, usize> = HashMap::new();
     let mut gates: Vec<(Gate<F>, usize, usize, usize)> = Vec::new();
 
-    let r = Constrains::eval_exprs(&pitagoras, &mut vars, &mut gates);
+    let _r = Constrains::eval_exprs(&pitagoras, &mut vars, &mut gates);
     let mut vars_rev = HashMap::new();
     vars.iter().for_each(|(k, v)| {
         vars_rev.insert(*v, k);
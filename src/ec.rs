@@ -4,6 +4,70 @@ use std::{
     ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign},
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldError {
+    /// The divisor is zero
+    DivisionByZero,
+    /// The value does not fit in the target field
+    OutOfRange { value: u64, modulus: u64 },
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldError::DivisionByZero => write!(f, "field division by zero"),
+            FieldError::OutOfRange { value, modulus } => {
+                write!(f, "{} does not fit in a field of order {}", value, modulus)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurveError {
+    /// The points cannot be added, it happens when they are not in the curve
+    InvalidAddition,
+}
+
+impl Display for CurveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CurveError::InvalidAddition => write!(f, "points cannot be added"),
+        }
+    }
+}
+
+impl std::error::Error for CurveError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The encoding does not have the expected number of bytes
+    InvalidLength { expected: usize, found: usize },
+    /// The encoding is not the canonical one, e.g. a value out of the field
+    NotCanonical,
+    /// The encoded point does not satisfy the curve equation
+    NotInCurve,
+    /// The encoded point is not in the expected subgroup
+    NotInSubgroup,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::InvalidLength { expected, found } => {
+                write!(f, "expected {} bytes, found {}", expected, found)
+            }
+            DecodeError::NotCanonical => write!(f, "not canonical encoding"),
+            DecodeError::NotInCurve => write!(f, "point not in curve"),
+            DecodeError::NotInSubgroup => write!(f, "point not in subgroup"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
 pub trait Field:
     Sized
     + Debug
@@ -25,8 +89,12 @@ pub trait Field:
     + Neg<Output = Self>
 {
     type Order: Display;
+    const MODULUS: u64;
 
     fn order() -> Self::Order;
+    fn characteristic() -> u64 {
+        Self::MODULUS
+    }
     fn zero() -> Self;
     fn is_zero(&self) -> bool;
     fn one() -> Self;
@@ -34,7 +102,44 @@ pub trait Field:
     fn in_field(&self) -> bool;
     // fn rebase<F1T: FieldT>(&self) -> F1T;
     fn inv(&self) -> Option<Self>;
+    /// Returns $2^k$, doubling `one()` inside the field so it never overflows
+    fn pow2(k: u32) -> Self {
+        (0..k).fold(Self::one(), |acc, _| acc + acc)
+    }
+    /// Returns the inverse, or zero for zero, so batch code can skip the branch
+    fn inv_or_zero(&self) -> Self {
+        self.inv().unwrap_or_else(Self::zero)
+    }
+    /// Divides by `rhs` like `/`, but returning an error so it can be used with `?`
+    fn try_div(&self, rhs: &Self) -> Result<Self, FieldError> {
+        (*self / *rhs).ok_or(FieldError::DivisionByZero)
+    }
+    /// Raises to `exp` by square and multiply, the number of operations depends
+    /// on the bits of `exp`, so only use it with public exponents
     fn pow(&self, exp: u64) -> Self;
+    /// Raises to `exp` with a fixed 4-bit window, always doing the same operations
+    /// whatever the value of `exp`, each window entry is selected by scanning the whole
+    /// table so memory accesses don't depend on `exp` either. It is only as constant
+    /// time as the field operations are
+    fn pow_ct(&self, exp: u64) -> Self {
+        let mut table = [Self::one(); 16];
+        for n in 1..16 {
+            table[n] = table[n - 1] * *self;
+        }
+        let mut result = Self::one();
+        for window in (0..16).rev() {
+            for _ in 0..4 {
+                result = result * result;
+            }
+            let nibble = (exp >> (window * 4)) & 0xf;
+            let entry = table.iter().zip(0u64..).fold(Self::zero(), |acc, (t, n)| {
+                acc + *t * Self::from((n == nibble) as u64)
+            });
+            result = result * entry;
+        }
+        result
+    }
+    fn sqrt(&self) -> Option<Self>;
     fn as_poly(&self) -> Poly<Self> {
         Poly::new(vec![*self])
     }
@@ -63,6 +168,18 @@ pub trait G1Point:
     fn y(&self) -> &Self::F;
     fn in_curve(&self) -> bool;
     fn is_identity(&self) -> bool;
+    fn decompress(x: Self::F, y_is_odd: bool) -> Option<Self>;
+    fn cofactor() -> Self::S;
+    fn in_subgroup(&self) -> bool {
+        (*self * Self::S::from(Self::generator_subgroup_size().as_u64())).is_identity()
+    }
+    fn clear_cofactor(&self) -> Self {
+        *self * Self::cofactor()
+    }
+    /// Returns if both points are the same after clearing the cofactor
+    fn is_equal_subgroup(&self, other: &Self) -> bool {
+        self.clear_cofactor() == other.clear_cofactor()
+    }
 }
 
 pub trait G2Point:
@@ -91,3 +208,15 @@ pub trait Pairing {
 
     fn pairing(p: Self::G1, q: Self::G2) -> Self::GT;
 }
+
+/// Bundles the groups, base field and pairing of a pairing friendly curve, the scalars
+/// are the `S` types of the groups
+pub trait Curve {
+    /// The base field, where the point coordinates live
+    type Fq: Field;
+    type G1: G1Point<F = Self::Fq>;
+    type G2: G2Point<F = Self::Fq>;
+    type GT: GTPoint;
+
+    fn pairing(p: &Self::G1, q: &Self::G2) -> Self::GT;
+}
@@ -1,8 +1,9 @@
-
 use crate::ec::Field;
 use crate::matrix::Matrix;
 use crate::poly::Poly;
+use anyhow::{anyhow, Result};
 
+#[derive(Debug, Clone, Copy)]
 pub struct EvaluationDomainGenerator<F: Field> {
     omega: F,
     size: usize,
@@ -12,6 +13,131 @@ impl<F: Field> EvaluationDomainGenerator<F> {
     pub fn new(omega: F, size: usize) -> Self {
         EvaluationDomainGenerator { omega, size }
     }
+    /// Creates the domain checking that `omega` has exactly order `size`, that is
+    /// $\omega^{size}=1$ and $\omega^{size/q} \neq 1$ for each prime factor $q$ of `size`
+    pub fn new_checked(omega: F, size: usize) -> Option<Self> {
+        let domain = Self::new(omega, size);
+        if domain.has_exact_order() {
+            Some(domain)
+        } else {
+            None
+        }
+    }
+    fn has_exact_order(&self) -> bool {
+        if self.size == 0 || self.omega.pow(self.size as u64) != F::one() {
+            return false;
+        }
+        let (mut n, mut q) = (self.size, 2);
+        while n > 1 {
+            if n % q == 0 {
+                if self.omega.pow((self.size / q) as u64) == F::one() {
+                    return false;
+                }
+                while n % q == 0 {
+                    n /= q;
+                }
+            }
+            q += 1;
+        }
+        true
+    }
+    /// Returns if `x` is in the subgroup generated by `omega`, that is $x^{size}=1$
+    pub fn contains(&self, x: &F) -> bool {
+        x.pow(self.size as u64) == F::one()
+    }
+    /// Returns if both domains generate the same subgroup, since the multiplicative group
+    /// is cyclic, that happens when both generators have the same order
+    pub fn same_subgroup(&self, other: &Self) -> bool {
+        self.size == other.size && self.has_exact_order() && other.has_exact_order()
+    }
+    /// Returns if the coset `offset`·`self` is a union of cosets of `base`, and it is disjoint
+    /// from `self`, as needed for an evaluation domain over a trace domain `base`
+    pub fn is_coset_of(&self, base: &Self, offset: F) -> bool {
+        self.has_exact_order()
+            && base.has_exact_order()
+            && self.size.is_multiple_of(base.size)
+            && self.contains(&base.omega)
+            && !offset.is_zero()
+            && !self.contains(&offset)
+    }
+    /// Returns the number of elements of the domain
+    pub fn size(&self) -> usize {
+        self.size
+    }
+    /// Returns the domain elements $\omega^0, \omega^1, ..., \omega^{n-1}$
+    pub fn elements(&self) -> Vec<F> {
+        (0..self.size as u64).map(|n| self.omega.pow(n)).collect()
+    }
+    /// Interpolates the polinomial that takes the values `f(i)` at the first `n` domain
+    /// elements $\omega^i$, without building the values first
+    pub fn lagrange_from_fn<G: Fn(usize) -> F>(&self, n: usize, f: G) -> Poly<F> {
+        assert!(
+            n <= self.size,
+            "cannot take {} points from a domain of {}",
+            n,
+            self.size
+        );
+        let points: Vec<_> = (0..n).map(|i| (self.omega.pow(i as u64), f(i))).collect();
+        Poly::lagrange(&points)
+    }
+    /// Evaluates in place the polinomial with `coeffs` over the domain, using
+    /// an iterative radix-2 transform with `omega` as the root of unity
+    ///
+    /// Panics if the domain size is not a power of two equal to `coeffs.len()`
+    pub fn fft(&self, coeffs: &mut [F]) {
+        self.radix2(coeffs, self.omega);
+    }
+    /// Interpolates in place the coefficients of the polinomial that takes the
+    /// `evals` values over the domain, the inverse of [`fft`](Self::fft)
+    ///
+    /// Panics if the domain size is not a power of two equal to `evals.len()`
+    pub fn ifft(&self, evals: &mut [F]) {
+        self.radix2(evals, self.omega.inv().unwrap());
+        let size_inv = F::from(self.size as u64).inv().unwrap();
+        for v in evals.iter_mut() {
+            *v = *v * size_inv;
+        }
+    }
+    fn radix2(&self, values: &mut [F], omega: F) {
+        assert!(self.size.is_power_of_two(), "domain size must be 2^n");
+        assert_eq!(values.len(), self.size, "values must match domain size");
+
+        let n = values.len();
+        let bits = n.trailing_zeros();
+        for i in 1..n {
+            let j = i.reverse_bits() >> (usize::BITS - bits);
+            if i < j {
+                values.swap(i, j);
+            }
+        }
+        let mut len = 2;
+        while len <= n {
+            let w_len = omega.pow((n / len) as u64);
+            for chunk in values.chunks_mut(len) {
+                let mut w = F::one();
+                let (lo, hi) = chunk.split_at_mut(len / 2);
+                for (a, b) in lo.iter_mut().zip(hi.iter_mut()) {
+                    let t = w * *b;
+                    *b = *a - t;
+                    *a += t;
+                    w = w * w_len;
+                }
+            }
+            len *= 2;
+        }
+    }
+}
+
+/// Evaluates the vanishing polinomial of the `domain` at `x`, that is $x^n-1$,
+/// without building the polinomial
+pub fn eval_vanishing<F: Field>(domain: &EvaluationDomainGenerator<F>, x: &F) -> F {
+    x.pow(domain.size as u64) - F::one()
+}
+
+/// Evaluates the derivative of the vanishing polinomial of the `domain` at `x`,
+/// that is $n x^{n-1}$
+pub fn eval_vanishing_derivative<F: Field>(domain: &EvaluationDomainGenerator<F>, x: &F) -> F {
+    F::from(domain.size as u64) * x.pow(domain.size as u64 - 1)
 }
 
 pub trait FFT<F: Field> {
@@ -20,7 +146,8 @@ pub trait FFT<F: Field> {
     fn fft_inv(&self, freq: &[F]) -> Vec<F>;
 }
 
-struct VandermondeMatrix<F: Field>(Matrix<F>);
+#[cfg_attr(not(test), allow(dead_code))]
+pub(crate) struct VandermondeMatrix<F: Field>(Matrix<F>);
 
 impl<F: Field> VandermondeMatrix<F> {}
 
@@ -48,7 +175,7 @@ impl<F: Field> FFT<F> for VandermondeMatrix<F> {
     }
 }
 
-struct CooleyTurkey<F: Field> {
+pub(crate) struct CooleyTurkey<F: Field> {
     pows: Vec<F>,
 }
 
@@ -96,7 +223,7 @@ fn cooley_tukey_fft<F: Field>(vals: &[&F], domain: &[&F]) -> Vec<F> {
         let r = cooley_tukey_fft(&split(vals, false), &half_domain);
 
         let mut o = vec![F::zero(); vals.len()];
-        for (i, (x, y)) in l.into_iter().zip(r.into_iter()).enumerate() {
+        for (i, (x, y)) in l.into_iter().zip(r).enumerate() {
             let y_times_root = y * domain[i];
             o[i] = x + y_times_root;
             o[i + vals.len() / 2] = x - y_times_root;
@@ -105,6 +232,72 @@ fn cooley_tukey_fft<F: Field>(vals: &[&F], domain: &[&F]) -> Vec<F> {
     }
 }
 
+/// Returns the index of $-x$ in a domain of even size `len` generated by powers of $\omega$
+/// (or a coset of it), where $x$ is at `idx`, using that $\omega^{n/2}=-1$
+pub fn negated_index(idx: usize, len: usize) -> usize {
+    assert!(len.is_multiple_of(2), "domain size must be even");
+    (idx + len / 2) % len
+}
+
+/// Returns the index of the FRI sibling of `idx` in a layer of `layer_len` elements, the
+/// point $-x$ that folds with $x$ into the same point $x^2$ of the next layer
+pub fn sibling_index(idx: usize, layer_len: usize) -> usize {
+    negated_index(idx, layer_len)
+}
+
+/// Checks the FRI folding relation for a queried `x`, that the next layer value at $x^2$ is
+/// $\frac{f(x)+f(-x)}{2} + \beta \frac{f(x)-f(-x)}{2x}$, it is false if `x` is zero
+pub fn check_fold<F: Field>(fx: F, fminusx: F, next_val: F, x: F, beta: F) -> bool {
+    let two = F::from(2u64);
+    match (two.inv(), (two * x).inv()) {
+        (Some(two_inv), Some(two_x_inv)) => {
+            next_val == (fx + fminusx) * two_inv + beta * (fx - fminusx) * two_x_inv
+        }
+        _ => false,
+    }
+}
+
+/// Low degree extension of `values`, interpolates them over the `small` domain and evaluates
+/// the resulting polinomial over the coset `offset`·`large`, both domains must be powers of two
+/// and `offset` must be nonzero and not in `large`, otherwise the coset overlaps the domain
+pub fn low_degree_extension<F: Field>(
+    values: &[F],
+    small: &EvaluationDomainGenerator<F>,
+    large: &EvaluationDomainGenerator<F>,
+    offset: F,
+) -> Result<Vec<F>> {
+    if values.len() != small.size {
+        return Err(anyhow!("one value per domain element"));
+    }
+    if !small.size.is_power_of_two() || !large.size.is_power_of_two() {
+        return Err(anyhow!("domain sizes must be powers of two"));
+    }
+    if large.size < small.size {
+        return Err(anyhow!("cannot extend to a smaller domain"));
+    }
+    if offset.is_zero() {
+        return Err(anyhow!("coset offset cannot be zero"));
+    }
+    if offset.pow(large.size as u64) == F::one() {
+        return Err(anyhow!("coset offset {} is in the domain", offset));
+    }
+
+    Ok(Poly::from_evaluations(small, values).eval_coset(offset, large))
+}
+
+/// Finds a primitive `n`-th root of unity for a power of two `n`, if the
+/// multiplicative group of the field has a subgroup of that order
+pub fn root_of_unity<F: Field>(n: usize) -> Option<F> {
+    let p_1 = F::characteristic() - 1;
+    if !n.is_power_of_two() || p_1 % n as u64 != 0 {
+        return None;
+    }
+    // for a quadratic non-residue z, z^((p-1)/n) has order n
+    (2..F::characteristic())
+        .map(|z| F::from(z).pow(p_1 / n as u64))
+        .find(|w| n == 1 || w.pow(n as u64 / 2) != F::one())
+}
+
 // according https://stackoverflow.com/questions/52270320/implementing-fft-over-finite-fields
 pub fn mul_ntt<F: Field, FFTI: FFT<F>>(
     fft: FFTI,
@@ -167,6 +360,141 @@ mod test {
         assert_eq!(&current_values, &values[..]);
     }
 
+    #[test]
+    fn test_domain_fft_in_place() {
+        type F = U64Field<337>;
+        let domain = EvaluationDomainGenerator::new(F::from(85u64), 8);
+
+        let coeffs = [3, 1, 4, 1, 5, 9, 2, 6].map(|x| F::from(x as u64));
+        let mut values = coeffs.to_vec();
+        domain.fft(&mut values);
+        assert_eq!(values, CooleyTurkey::new(domain).fft(&coeffs));
+
+        domain.ifft(&mut values);
+        assert_eq!(values, coeffs);
+
+        // evaluations of the plonk-by-hand a(x) wire over the 4-th roots of unity in F17
+        type F17 = U64Field<17>;
+        let h = EvaluationDomainGenerator::new(F17::from(4u64), 4);
+        let p = Poly::<F17>::from(&[1, 13, 3, 3]);
+        let mut evals: Vec<F17> = h.elements().iter().map(|x| p.eval(x)).collect();
+        h.ifft(&mut evals);
+        assert_eq!(evals, p.coeffs());
+        h.fft(&mut evals);
+        assert_eq!(
+            evals,
+            h.elements().iter().map(|x| p.eval(x)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_domain_lagrange_from_fn() {
+        type F = U64Field<337>;
+        let domain = EvaluationDomainGenerator::new(F::from(85u64), 8);
+
+        let values = [3, 1, 4, 1, 5, 9, 2, 6].map(|x| F::from(x as u64));
+        let points: Vec<_> = domain.elements().into_iter().zip(values).collect();
+        assert_eq!(
+            domain.lagrange_from_fn(8, |i| values[i]),
+            Poly::lagrange(&points)
+        );
+        assert_eq!(
+            domain.lagrange_from_fn(3, |i| values[i]),
+            Poly::lagrange(&points[..3])
+        );
+
+        // the indicator of the row 2 is the lagrange basis L_2
+        let l_2 = domain.lagrange_from_fn(8, |i| if i == 2 { F::one() } else { F::zero() });
+        assert_eq!(l_2.degree(), 7);
+        for (i, x) in domain.elements().iter().enumerate() {
+            assert_eq!(l_2.eval(x), if i == 2 { F::one() } else { F::zero() });
+        }
+    }
+
+    #[test]
+    fn test_negated_index() {
+        let stark = crate::presets::stark101();
+        let eval_domain: Vec<_> = stark
+            .eval_domain
+            .elements()
+            .iter()
+            .map(|x| stark.generator * x)
+            .collect();
+        let half = eval_domain.len() / 2;
+        for idx in [0, 1, 2, 1000, half - 1, half, eval_domain.len() - 1] {
+            let neg_idx = negated_index(idx, eval_domain.len());
+            assert_eq!(eval_domain[neg_idx], -eval_domain[idx]);
+        }
+        assert_eq!(negated_index(3, 8), 7);
+        assert_eq!(negated_index(7, 8), 3);
+    }
+
+    #[test]
+    fn test_check_fold() {
+        use crate::presets::Stark101Field;
+        let stark = crate::presets::stark101();
+        let beta = Stark101Field::from(3141592u64);
+
+        let mut f = Poly::new(
+            (0..1024u64)
+                .map(|n| Stark101Field::from(n * n + 7))
+                .collect(),
+        );
+        let (mut domain, mut offset) = (stark.eval_domain, stark.generator);
+        for _ in 0..3 {
+            let layer = f.eval_coset(offset, &domain);
+            let (layer_offset, omega) = (offset, domain.elements()[1]);
+
+            // fold the even and odd coefficients, f(x) = even(x^2) + x·odd(x^2)
+            f = Poly::new(
+                f.coeffs()
+                    .chunks(2)
+                    .map(|c| c[0] + beta * c.get(1).copied().unwrap_or_else(Stark101Field::zero))
+                    .collect(),
+            );
+            domain = EvaluationDomainGenerator::new(omega * omega, domain.size() / 2);
+            offset = offset * offset;
+            let next = f.eval_coset(offset, &domain);
+
+            for idx in [0, 1, 1000, layer.len() - 1] {
+                let x = layer_offset * omega.pow(idx as u64);
+                let (fx, fminusx) = (layer[idx], layer[sibling_index(idx, layer.len())]);
+                let next_val = next[idx % next.len()];
+                assert!(check_fold(fx, fminusx, next_val, x, beta));
+                assert!(!check_fold(
+                    fx,
+                    fminusx,
+                    next_val + Stark101Field::one(),
+                    x,
+                    beta
+                ));
+            }
+        }
+        assert!(!check_fold(beta, beta, beta, Stark101Field::zero(), beta));
+    }
+
+    #[test]
+    fn test_sibling_index() {
+        let stark = crate::presets::stark101();
+        let mut layer: Vec<_> = stark
+            .eval_domain
+            .elements()
+            .iter()
+            .map(|x| stark.generator * x)
+            .collect();
+        while layer.len() > 1 {
+            let next: Vec<_> = layer[..layer.len() / 2].iter().map(|x| *x * x).collect();
+            for idx in [0, 1, layer.len() / 2 - 1, layer.len() - 1] {
+                let sibling = sibling_index(idx, layer.len());
+                assert_eq!(sibling_index(sibling, layer.len()), idx);
+                // x and -x fold into the same point of the next layer
+                assert_eq!(next[idx % next.len()], layer[idx] * layer[idx]);
+                assert_eq!(next[sibling % next.len()], layer[idx] * layer[idx]);
+            }
+            layer = next;
+        }
+    }
+
     #[test]
     fn test_ntt_poly_mul() {
         type F = U64Field<337>;
@@ -181,4 +509,99 @@ mod test {
 
         assert_eq!(poly_c, ntt_c);
     }
+
+    #[test]
+    fn test_domain_new_checked() {
+        type F = U64Field<337>;
+        assert!(EvaluationDomainGenerator::new_checked(F::from(85u64), 8).is_some());
+        // 85^2 has order 4, and 1 has order 1
+        assert!(EvaluationDomainGenerator::new_checked(F::from(85u64).pow(2), 8).is_none());
+        assert!(EvaluationDomainGenerator::new_checked(F::one(), 8).is_none());
+        // 2 does not have order 8
+        assert!(EvaluationDomainGenerator::new_checked(F::from(2u64), 8).is_none());
+        // 85^2 has order 4
+        assert!(EvaluationDomainGenerator::new_checked(F::from(85u64).pow(2), 4).is_some());
+
+        // 2 generates the whole F101*, that has order 100=2^2*5^2
+        type F101 = U64Field<101>;
+        assert!(EvaluationDomainGenerator::new_checked(F101::from(2u64), 100).is_some());
+        assert!(EvaluationDomainGenerator::new_checked(F101::from(2u64).pow(5), 100).is_none());
+        assert!(EvaluationDomainGenerator::new_checked(F101::from(2u64).pow(5), 20).is_some());
+    }
+
+    #[test]
+    fn test_domain_subgroups_and_cosets() {
+        let stark = crate::presets::stark101();
+        let (trace, eval, w) = (stark.trace_domain, stark.eval_domain, stark.generator);
+
+        // g^3 also generates the trace subgroup, but g^2 only has order 512
+        let g = trace.elements()[1];
+        assert!(trace.same_subgroup(&EvaluationDomainGenerator::new(g.pow(3), 1024)));
+        assert!(!trace.same_subgroup(&EvaluationDomainGenerator::new(g.pow(2), 1024)));
+        assert!(!trace.same_subgroup(&eval));
+
+        assert!(eval.contains(&g));
+        assert!(!trace.contains(&w));
+        assert!(eval.is_coset_of(&trace, w));
+        // a zero offset, an offset inside the domain, or a trace domain not in it, are setup mistakes
+        assert!(!eval.is_coset_of(&trace, g));
+        assert!(!eval.is_coset_of(&trace, crate::presets::Stark101Field::zero()));
+        assert!(!trace.is_coset_of(&eval, w));
+        assert!(!eval.is_coset_of(&EvaluationDomainGenerator::new(w, 1024), w));
+    }
+
+    #[test]
+    fn test_low_degree_extension() {
+        type F = U64Field<337>;
+        let large = EvaluationDomainGenerator::new(F::from(85u64), 8);
+        let small = EvaluationDomainGenerator::new(F::from(85u64).pow(2), 4);
+        let values = [3, 1, 4, 1].map(|x| F::from(x as u64));
+        let offset = F::from(10u64);
+
+        let p = Poly::lagrange(
+            &small
+                .elements()
+                .into_iter()
+                .zip(values.iter().copied())
+                .collect::<Vec<_>>(),
+        );
+        let expected: Vec<_> = large
+            .elements()
+            .iter()
+            .map(|x| p.eval(&(offset * x)))
+            .collect();
+
+        assert_eq!(
+            low_degree_extension(&values, &small, &large, offset).unwrap(),
+            expected
+        );
+
+        // 5 is not in the domain, but 85^2 and 1 are
+        assert!(low_degree_extension(&values, &small, &large, F::from(5u64)).is_ok());
+        assert!(low_degree_extension(&values, &small, &large, F::from(85u64).pow(2)).is_err());
+        assert!(low_degree_extension(&values, &small, &large, F::one()).is_err());
+        assert!(low_degree_extension(&values, &small, &large, F::zero()).is_err());
+        assert!(low_degree_extension(&values[1..], &small, &large, offset).is_err());
+    }
+
+    #[test]
+    fn test_eval_vanishing() {
+        type F = U64Field<337>;
+        let domain = EvaluationDomainGenerator::new(F::from(85u64), 8);
+        let z_h = Poly::z(&domain.elements());
+
+        // derivative of the vanishing polinomial x^8-1
+        let z_h_derivative = Poly::<F>::from(&[0, 0, 0, 0, 0, 0, 0, 8]);
+
+        for x in [0u64, 1, 2, 85, 100, 336].iter().map(|x| F::from(*x)) {
+            assert_eq!(eval_vanishing(&domain, &x), z_h.eval(&x));
+            assert_eq!(
+                eval_vanishing_derivative(&domain, &x),
+                z_h_derivative.eval(&x)
+            );
+        }
+        for x in domain.elements() {
+            assert!(eval_vanishing(&domain, &x).is_zero());
+        }
+    }
 }
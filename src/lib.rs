@@ -5,4 +5,5 @@ pub mod matrix;
 pub mod pbh;
 pub mod plonk;
 pub mod poly;
+pub mod presets;
 pub mod utils;
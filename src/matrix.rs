@@ -187,9 +187,9 @@ impl<F: Field> From<Poly<F>> for Matrix<F> {
     }
 }
 
-impl<F: Field> Into<Vec<F>> for Matrix<F> {
-    fn into(self) -> Vec<F> {
-        self.v
+impl<F: Field> From<Matrix<F>> for Vec<F> {
+    fn from(matrix: Matrix<F>) -> Self {
+        matrix.v
     }
 }
 
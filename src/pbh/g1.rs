@@ -1,21 +1,92 @@
 use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    convert::TryFrom,
     fmt::Display,
-    hash::Hash,
-    ops::{Add, Mul, Neg},
+    hash::{Hash, Hasher},
+    ops::{Add, Mul, Neg, Sub},
+    str::FromStr,
 };
 
+use anyhow::{anyhow, Result};
+
 use super::{f101, F101};
-use crate::ec::{Field, G1Point};
+use crate::ec::{CurveError, DecodeError, Field, G1Point};
 
 #[allow(non_snake_case)]
 pub fn g1f(x: u64, y: u64) -> G1P {
     G1P::new(F101::from(x), F101::from(y))
 }
 
+/// Precomputed doublings $g, 2g, 4g, 8g, 16g$ of the generator, enough to
+/// multiply by any scalar of the order 17 subgroup
+pub struct FixedBaseTable(Vec<G1P>);
+
+/// Creates the doublings table for the generator $g=(1,2)$
+pub fn generator_table() -> FixedBaseTable {
+    let order = G1P::generator_subgroup_size().as_u64();
+    let mut table = vec![G1P::generator()];
+    while 1 << table.len() < order {
+        let last = table[table.len() - 1];
+        table.push(last + last);
+    }
+    FixedBaseTable(table)
+}
+
+/// Multiplies the generator by `k` adding the precomputed doublings in the `table`
+pub fn mul_fixed_base(table: &FixedBaseTable, k: F101) -> G1P {
+    let k = k.as_u64() % G1P::generator_subgroup_size().as_u64();
+    table
+        .0
+        .iter()
+        .enumerate()
+        .filter(|(n, _)| k >> n & 1 == 1)
+        .fold(G1P::identity(), |acc, (_, p)| acc + *p)
+}
+
+/// Returns all the points in the curve, the point at infinity first
+pub fn curve_points() -> Vec<G1P> {
+    let mut points = vec![G1P::identity()];
+    for x in 0..101 {
+        for y in 0..101 {
+            let p = G1P::new(f101(x), f101(y));
+            if p.in_curve() {
+                points.push(p);
+            }
+        }
+    }
+    points
+}
+
+/// Returns the cyclic subgroups of the curve with their sorted elements, each one
+/// indexed by its smallest generator, so the order 17 subgroup is indexed by $(1,2)$
+pub fn subgroups() -> HashMap<G1P, Vec<G1P>> {
+    let mut generators: HashMap<Vec<G1P>, G1P> = HashMap::new();
+    for g in curve_points() {
+        // add g until getting back to g, identity included
+        let mut elements = vec![g];
+        let mut acc = g + g;
+        while acc != g {
+            elements.push(acc);
+            acc = acc + g;
+        }
+        elements.sort();
+        generators
+            .entry(elements)
+            .and_modify(|min| *min = (*min).min(g))
+            .or_insert(g);
+    }
+    generators.into_iter().map(|(e, g)| (g, e)).collect()
+}
+
 /// A point in the $y^2+x^3+3$ curve, on the $\mathbb{F}_{101}$ field.
 /// The generator $g=(1,2)$ generates a subgroup of order 17: $17g=g$
 ///
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+/// The point at infinity is stored as `infinite` with $(0,0)$ coordinates, that are
+/// not a solution of the curve equation and are ignored by equality, hashing and ordering.
+/// Points are ordered by their compressed encoding, so the point at infinity sorts first
+///
+#[derive(Debug, Copy, Clone)]
 pub struct G1P {
     /// x coordinate
     pub x: F101,
@@ -59,14 +130,26 @@ impl G1Point for G1P {
             infinite: false,
         }
     }
-    /// Checks if the coordinates are on the curve, so $y^2 = x^3 +3$
+    /// Checks if the coordinates are on the curve, so $y^2 = x^3 +3$, the point at infinity
+    /// is always on the curve
     fn in_curve(&self) -> bool {
-        self.y.pow(2) == self.x.pow(3) + f101(3u64)
+        self.infinite || self.y.pow(2) == self.x.pow(3) + f101(3u64)
     }
     /// Checks if the point is at infinity
     fn is_identity(&self) -> bool {
         self.infinite
     }
+    /// Recovers the point from its $x$ coordinate, computing $y=\pm\sqrt{x^3+3}$ and
+    /// selecting the root with the requested parity
+    fn decompress(x: Self::F, y_is_odd: bool) -> Option<Self> {
+        let y = (x.pow(3) + f101(3u64)).sqrt()?;
+        // for y=0 both roots are the same, and there is no odd one
+        [y, -y]
+            .iter()
+            .copied()
+            .find(|y| (y.as_u64() % 2 == 1) == y_is_odd)
+            .map(|y| G1P::new(x, y))
+    }
     /// Returns the generator $g=(1,2)$
     fn generator() -> Self {
         G1P {
@@ -79,6 +162,10 @@ impl G1Point for G1P {
     fn generator_subgroup_size() -> Self::F {
         f101(17u64)
     }
+    /// Returns the cofactor of the subgroup, the curve has $102=6 \cdot 17$ points
+    fn cofactor() -> Self::S {
+        f101(6u64)
+    }
     /// Returns the point at infinity
     fn identity() -> Self {
         G1P {
@@ -95,6 +182,92 @@ impl G1Point for G1P {
     }
 }
 
+impl G1P {
+    /// Adds both points, failing if they have the same $x$ but are neither equal
+    /// nor opposite, that only happens if they are not in the curve
+    pub fn try_add(self, rhs: G1P) -> Result<G1P, CurveError> {
+        if self.infinite {
+            Ok(rhs)
+        } else if rhs.infinite {
+            Ok(self)
+        } else if self == -rhs {
+            Ok(G1P::identity())
+        } else if self == rhs {
+            Ok(self.double())
+        } else {
+            // https://en.wikipedia.org/wiki/Elliptic_curve_point_multiplication#G1P_addition
+            let lambda =
+                ((rhs.y - self.y) / (rhs.x - self.x)).ok_or(CurveError::InvalidAddition)?;
+            let x = lambda.pow(2) - self.x - rhs.x;
+            Ok(G1P::new(x, lambda * (self.x - x) - self.y))
+        }
+    }
+
+    /// Returns $2P=(m^2-2x, m(3x-m^2)-y)$ where $m=\frac{3x^2}{2y}$, points with $y=0$
+    /// have order two, so their double is the identity
+    pub fn double(&self) -> G1P {
+        if self.infinite || self.y.is_zero() {
+            return G1P::identity();
+        }
+        let two = f101(2);
+        let three = f101(3);
+        let m = ((three * self.x.pow(2)) / (two * self.y)).unwrap();
+        G1P::new(
+            m * m - two * self.x,
+            m * (three * self.x - m.pow(2)) - self.y,
+        )
+    }
+
+    /// Returns the compressed encoding of the point, `[0x02, x]` if $y$ is even,
+    /// `[0x03, x]` if $y$ is odd, and `[0x00, 0x00]` for the point at infinity.
+    /// $x$ fits in one byte, so the encoding is the same in big and little endian
+    pub fn to_compressed(&self) -> [u8; 2] {
+        if self.infinite {
+            [0x00, 0x00]
+        } else {
+            [0x02 | (self.y.as_u64() % 2) as u8, self.x.as_u64() as u8]
+        }
+    }
+
+    // coordinates used for comparisons, all points at infinity are the same point
+    fn key(&self) -> (F101, F101, bool) {
+        if self.infinite {
+            (F101::zero(), F101::zero(), true)
+        } else {
+            (self.x, self.y, false)
+        }
+    }
+}
+
+impl PartialEq for G1P {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for G1P {}
+
+impl Hash for G1P {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+
+impl PartialOrd for G1P {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for G1P {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // y only breaks ties between points that are not in the curve
+        self.to_compressed()
+            .cmp(&other.to_compressed())
+            .then_with(|| self.key().cmp(&other.key()))
+    }
+}
+
 impl Display for G1P {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         if self.infinite {
@@ -105,6 +278,61 @@ impl Display for G1P {
     }
 }
 
+/// Decodes the compressed encoding returned by `to_compressed`
+impl TryFrom<&[u8]> for G1P {
+    type Error = DecodeError;
+    fn try_from(bytes: &[u8]) -> std::result::Result<Self, Self::Error> {
+        let (tag, x) = match bytes {
+            [tag, x] => (*tag, *x as u64),
+            _ => {
+                return Err(DecodeError::InvalidLength {
+                    expected: 2,
+                    found: bytes.len(),
+                })
+            }
+        };
+        match tag {
+            0x00 if x == 0 => Ok(G1P::identity()),
+            0x02 | 0x03 if x < 101 => {
+                // a point with y=0 only has the even encoding
+                G1P::decompress(f101(x), tag == 0x03).ok_or_else(|| {
+                    match G1P::decompress(f101(x), tag == 0x02) {
+                        Some(_) => DecodeError::NotCanonical,
+                        None => DecodeError::NotInCurve,
+                    }
+                })
+            }
+            _ => Err(DecodeError::NotCanonical),
+        }
+    }
+}
+
+/// Parses the `Display` representation, `infinite` or `(x,y)`
+impl FromStr for G1P {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        if s == "infinite" {
+            return Ok(G1P::identity());
+        }
+        let coord = |v: &str| -> Result<F101> {
+            match v.trim().parse::<u64>() {
+                Ok(n) if n < 101 => Ok(f101(n)),
+                _ => Err(anyhow!("invalid coordinate '{}'", v)),
+            }
+        };
+        let (x, y) = s
+            .strip_prefix('(')
+            .and_then(|s| s.strip_suffix(')'))
+            .and_then(|s| s.split_once(','))
+            .ok_or_else(|| anyhow!("malformed point '{}'", s))?;
+        let p = G1P::new(coord(x)?, coord(y)?);
+        if !p.in_curve() {
+            return Err(anyhow!("point {} is not in the curve", p));
+        }
+        Ok(p)
+    }
+}
+
 impl Neg for G1P {
     type Output = G1P;
     fn neg(self) -> Self::Output {
@@ -119,27 +347,15 @@ impl Neg for G1P {
 impl Add for G1P {
     type Output = G1P;
     fn add(self, rhs: G1P) -> Self {
-        if self.infinite {
-            rhs
-        } else if rhs.infinite {
-            self
-        } else if self == -rhs {
-            G1P::identity()
-        } else if self == rhs {
-            let two = f101(2);
-            let three = f101(3);
-            let m = ((three * self.x.pow(2)) / (two * self.y)).unwrap();
-            G1P::new(
-                m * m - two * self.x,
-                m * (three * self.x - m.pow(2)) - self.y,
-            )
-        } else {
-            // https://en.wikipedia.org/wiki/Elliptic_curve_point_multiplication#G1P_addition
-            let lambda = ((rhs.y - self.y) / (rhs.x - self.x))
-                .unwrap_or_else(|| panic!("cannot add {}+{}", self, rhs));
-            let x = lambda.pow(2) - self.x - rhs.x;
-            G1P::new(x, lambda * (self.x - x) - self.y)
-        }
+        self.try_add(rhs)
+            .unwrap_or_else(|e| panic!("cannot add {}+{}: {}", self, rhs, e))
+    }
+}
+
+impl Sub for G1P {
+    type Output = G1P;
+    fn sub(self, rhs: G1P) -> Self {
+        self + (-rhs)
     }
 }
 
@@ -161,69 +377,42 @@ impl Mul<F101> for G1P {
                 })
             }
             rhs >>= 1;
-            base = base + base;
+            base = base.double();
         }
         result.unwrap()
     }
 }
 
+impl Mul<G1P> for F101 {
+    type Output = G1P;
+    fn mul(self, rhs: G1P) -> Self::Output {
+        rhs * self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_find_subgroups() {
-        // add all points that are in the curve
-        let mut points = Vec::new();
-        for x in 0..101 {
-            for y in 0..101 {
-                let p = G1P::new(f101(x), f101(y));
-                if p.in_curve() {
-                    points.push(p);
-                }
-            }
-        }
-
-        // find subgroups
-        let mut subgroups = std::collections::HashMap::new();
-        while points.len() > 0 {
-            let mut in_subgroup = Vec::new();
-
-            // pick one element as a generator
-            let g = points.pop().unwrap();
-            in_subgroup.push(g);
-
-            // find all m * g != g
-            let mut mul = 2;
-            let mut gmul = g * f101(mul);
-            while g != gmul {
-                in_subgroup.push(gmul);
-                mul += 1;
-                gmul = g * f101(mul);
-            }
-            in_subgroup.sort();
-            subgroups.insert(g, in_subgroup);
-        }
-
-        // find unique subgroups
-        let mut duplicates = Vec::new();
-        for (g, e) in &subgroups {
-            if !duplicates.contains(g) {
-                subgroups
-                    .iter()
-                    .filter(|(g1, e1)| g1 != &g && e1 == &e)
-                    .for_each(|(g, _)| duplicates.push(*g));
-            }
-        }
+        let points = curve_points();
+        assert_eq!(points.len(), 102);
+        assert_eq!(points[0], G1P::identity());
 
-        // remove duplicates
-        subgroups.retain(|g, _| !duplicates.contains(g));
+        // the curve group is cyclic of order 102=2·3·17, one subgroup per divisor
+        let subgroups = subgroups();
+        let mut orders: Vec<_> = subgroups.values().map(|e| e.len()).collect();
+        orders.sort_unstable();
+        assert_eq!(orders, vec![1, 2, 3, 6, 17, 34, 51, 102]);
+        assert_eq!(subgroups[&G1P::generator()].len(), 17);
+        assert!(subgroups[&G1P::generator()].iter().all(|p| p.in_subgroup()));
 
         for (g, e) in &subgroups {
             println!("{} {}", g, e.len());
             if e.len() < 20 {
-                for n in 0..e.len() {
-                    println!("  {}", e[n]);
+                for p in e {
+                    println!("  {}", p);
                 }
             }
         }
@@ -258,4 +447,196 @@ mod tests {
         assert_eq!(g * f101(2), g + g);
         assert_eq!(g * f101(6), g + g + g + g + g + g);
     }
+
+    #[test]
+    fn test_g1_try_from_bytes() {
+        let g = G1P::generator();
+        for n in 0..17 {
+            let p = g * f101(n);
+            assert_eq!(G1P::try_from(&p.to_compressed()[..]), Ok(p));
+        }
+        assert_eq!(
+            G1P::try_from(&[0x02u8][..]),
+            Err(DecodeError::InvalidLength {
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            G1P::try_from(&[0x02u8, 101][..]),
+            Err(DecodeError::NotCanonical)
+        );
+        assert_eq!(
+            G1P::try_from(&[0x04u8, 1][..]),
+            Err(DecodeError::NotCanonical)
+        );
+        assert_eq!(
+            G1P::try_from(&[0x00u8, 1][..]),
+            Err(DecodeError::NotCanonical)
+        );
+        // (48,0) is the only point with y=0, so it has no odd encoding
+        assert!(G1P::try_from(&[0x02u8, 48][..]).is_ok());
+        assert_eq!(
+            G1P::try_from(&[0x03u8, 48][..]),
+            Err(DecodeError::NotCanonical)
+        );
+        // x^3+3 is not a square for x=2
+        assert_eq!(
+            G1P::try_from(&[0x02u8, 2][..]),
+            Err(DecodeError::NotInCurve)
+        );
+    }
+
+    #[test]
+    fn test_g1_from_str() {
+        let g = G1P::generator();
+        for n in 0..17 {
+            let p = g * f101(n);
+            assert_eq!(p.to_string().parse::<G1P>().unwrap(), p);
+        }
+        assert_eq!("infinite".parse::<G1P>().unwrap(), G1P::identity());
+        assert_eq!("(68,74)".parse::<G1P>().unwrap(), g1f(68, 74));
+
+        for s in &[
+            "(101,2)", "(1,102)", "(1,2", "1,2", "(1;2)", "(1,2,3)", "(a,2)", "(1,3)", "",
+        ] {
+            assert!(s.parse::<G1P>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_g1_try_add() {
+        let g = G1P::generator();
+        assert_eq!(g.try_add(g1f(68, 74)), Ok(g1f(26, 45)));
+        assert_eq!(g.try_add(-g), Ok(G1P::identity()));
+        // (1,5) is not in the curve, and has the same x than g
+        assert_eq!(g.try_add(g1f(1, 5)), Err(CurveError::InvalidAddition));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot add")]
+    fn test_g1_add_unchecked_panics() {
+        let _ = G1P::generator() + g1f(1, 5);
+    }
+
+    #[test]
+    fn test_g1_sub() {
+        let g = G1P::generator();
+        let two_g = g1f(68, 74);
+        assert_eq!(two_g - g, g);
+        assert_eq!(g - g, G1P::identity());
+        assert_eq!(g - two_g, -g);
+        assert_eq!(G1P::identity() - g, -g);
+
+        // (48,0) has order 2, so it is cleared by the cofactor
+        let t = g1f(48, 0);
+        assert!(t.in_curve());
+        assert_ne!(g + t, g);
+        assert!((g + t).is_equal_subgroup(&g));
+        assert!(!two_g.is_equal_subgroup(&g));
+    }
+
+    #[test]
+    fn test_g1_double() {
+        let g = G1P::generator();
+        assert_eq!(g.double(), g1f(68, 74));
+        assert_eq!(g.double().double(), g1f(65, 98));
+        assert_eq!(g.double().double().double(), g1f(18, 49));
+        assert_eq!(G1P::identity().double(), G1P::identity());
+        assert_eq!(g1f(48, 0).double(), G1P::identity());
+        for n in 0..17 {
+            let p = g * f101(n);
+            assert_eq!(p.double(), p + p);
+        }
+    }
+
+    #[test]
+    fn test_g1_scalar_mul_commutes() {
+        let g = G1P::generator();
+        assert_eq!(f101(5) * g, g * f101(5));
+
+        let points = [g, g1f(68, 74), g1f(65, 98)];
+        let scalars = [f101(3), f101(7), f101(11)];
+        let msm = scalars
+            .iter()
+            .zip(points.iter())
+            .fold(G1P::identity(), |acc, (a, p)| acc + *a * *p);
+        assert_eq!(msm, g * f101(3 + 7 * 2 + 11 * 4));
+    }
+
+    #[test]
+    fn test_g1_fixed_base() {
+        let table = generator_table();
+        assert_eq!(table.0.len(), 5);
+        for k in 0..101 {
+            assert_eq!(mul_fixed_base(&table, f101(k)), G1P::generator() * f101(k));
+        }
+    }
+
+    #[test]
+    fn test_g1_clear_cofactor() {
+        let points = curve_points();
+        assert!(points.iter().any(|p| !p.in_subgroup()));
+        assert!(points.iter().all(|p| p.clear_cofactor().in_subgroup()));
+        assert!(G1P::generator().in_subgroup());
+    }
+
+    #[test]
+    fn test_g1_ord() {
+        let g = G1P::generator();
+        let mut points: Vec<_> = (0..17).map(|n| g * f101(n)).collect();
+        points.sort();
+
+        assert_eq!(points[0], G1P::identity());
+        assert!(points.windows(2).all(|w| w[0] < w[1]));
+        assert!(points
+            .windows(2)
+            .all(|w| w[0].to_compressed() < w[1].to_compressed()));
+
+        // (1,2) has even y, (1,99) has odd y
+        assert!(g1f(1, 2) < g1f(1, 99));
+        assert!(g1f(1, 99) > g1f(68, 74));
+        assert_eq!(G1P::identity().cmp(&(g + (-g))), std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn test_g1_identity_in_curve() {
+        let g = G1P::generator();
+        assert!(G1P::identity().in_curve());
+        assert!((g + (-g)).in_curve());
+        assert!((g * f101(17)).in_curve());
+        assert!(!G1P::new(f101(0), f101(0)).in_curve());
+    }
+
+    #[test]
+    fn test_g1_identity_eq() {
+        let g = G1P::generator();
+        let stale = G1P {
+            x: f101(5),
+            y: f101(7),
+            infinite: true,
+        };
+        assert_eq!(G1P::identity(), stale);
+        assert_eq!(G1P::identity(), g + (-g));
+        assert_eq!(stale, g + (-g));
+        assert_ne!(stale, G1P::new(f101(5), f101(7)));
+
+        let mut set = std::collections::HashSet::new();
+        set.insert(G1P::identity());
+        assert!(set.contains(&stale));
+    }
+
+    #[test]
+    fn test_g1_decompress() {
+        let g = G1P::generator();
+        for n in 1..17 {
+            let p = g * f101(n);
+            assert_eq!(Some(p), G1P::decompress(p.x, p.y.as_u64() % 2 == 1));
+        }
+        // x^3+3 is not a quadratic residue for x=2
+        assert_eq!(None, G1P::decompress(f101(2), false));
+        // (48,0) is the only point with x=48, and 0 is even
+        assert_eq!(Some(g1f(48, 0)), G1P::decompress(f101(48), false));
+        assert_eq!(None, G1P::decompress(f101(48), true));
+    }
 }
@@ -1,10 +1,11 @@
 use std::{
+    convert::TryFrom,
     fmt::Display,
     ops::{Add, Mul, Neg},
 };
 
 use super::{f101, F101};
-use crate::ec::{Field, G2Point};
+use crate::ec::{DecodeError, Field, G2Point};
 
 #[allow(non_snake_case)]
 pub fn g2f(a: u64, b: u64) -> G2P {
@@ -48,6 +49,66 @@ impl Display for G2P {
     }
 }
 
+impl G2P {
+    /// Checks if $(a, bu)$ is in the curve, so $-2b^2 = a^3+3$
+    pub fn in_curve(&self) -> bool {
+        -f101(2) * self.b.pow(2) == self.a.pow(3) + f101(3)
+    }
+
+    /// Checks if the point is in the order 17 subgroup. Since there is no point at
+    /// infinity, $17Q=O$ is checked as $16Q=-Q$, points with $b=0$ have order two
+    pub fn in_subgroup(&self) -> bool {
+        self.in_curve() && !self.b.is_zero() && *self * f101(16) == -*self
+    }
+
+    /// Returns the compressed encoding of the point, `[0x02, a]` if $b$ is even
+    /// and `[0x03, a]` if $b$ is odd
+    pub fn to_compressed(&self) -> [u8; 2] {
+        [0x02 | (self.b.as_u64() % 2) as u8, self.a.as_u64() as u8]
+    }
+
+    /// Returns the point $(a, bu)$ in the curve with the selected parity of $b$
+    pub fn decompress(a: F101, b_is_odd: bool) -> Option<Self> {
+        let b = ((a.pow(3) + f101(3)) / -f101(2))?.sqrt()?;
+        // for b=0 both roots are the same, and there is no odd one
+        [b, -b]
+            .iter()
+            .copied()
+            .find(|b| (b.as_u64() % 2 == 1) == b_is_odd)
+            .map(|b| G2P::new(a, b))
+    }
+}
+
+/// Decodes the compressed encoding returned by `to_compressed`, only accepting
+/// points in the order 17 subgroup
+impl TryFrom<&[u8]> for G2P {
+    type Error = DecodeError;
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let (tag, a) = match bytes {
+            [tag @ (0x02 | 0x03), a] if (*a as u64) < 101 => (*tag, *a as u64),
+            [_, _] => return Err(DecodeError::NotCanonical),
+            _ => {
+                return Err(DecodeError::InvalidLength {
+                    expected: 2,
+                    found: bytes.len(),
+                })
+            }
+        };
+        // a point with b=0 only has the even encoding
+        let q = G2P::decompress(f101(a), tag == 0x03).ok_or_else(|| {
+            match G2P::decompress(f101(a), tag == 0x02) {
+                Some(_) => DecodeError::NotCanonical,
+                None => DecodeError::NotInCurve,
+            }
+        })?;
+        if !q.in_subgroup() {
+            Err(DecodeError::NotInSubgroup)
+        } else {
+            Ok(q)
+        }
+    }
+}
+
 impl Neg for G2P {
     type Output = G2P;
     fn neg(self) -> Self::Output {
@@ -107,14 +168,80 @@ mod tests {
     #[test]
     fn test_g2_vectors() {
         let g = G2P::generator();
+        let two_g = g + g;
+        let four_g = two_g + two_g;
+        let eight_g = four_g + four_g;
+        let sixteen_g = eight_g + eight_g;
 
         // check point doubling
-        assert_eq!(g2f(90, 82), g + g);
+        assert_eq!(g2f(36, 70), -g);
+        assert_eq!(g2f(90, 82), two_g);
+        assert_eq!(g2f(63, 35), four_g);
+        assert_eq!(g2f(2, 34), eight_g);
+
+        // since g = -16 g, this subgroup has order 17
+        assert_eq!(sixteen_g, -g);
 
         // check point addition
+        assert_eq!(g2f(10, 16), two_g + g);
         assert_eq!((g + g) + (g + g), g + g + g + g);
 
         // check point multiplication
+        assert_eq!(g * f101(16), sixteen_g);
         assert_eq!(g * f101(6), g + g + g + g + g + g);
     }
+
+    #[test]
+    fn test_g2_compressed() {
+        let g = G2P::generator();
+        assert!(g.in_curve() && g.in_subgroup());
+        for n in 1..17 {
+            let q = g * f101(n);
+            assert_eq!(G2P::try_from(&q.to_compressed()[..]), Ok(q));
+        }
+
+        // the twist has 102 points, only 16 (and the missing identity) are in the subgroup
+        let (mut in_subgroup, mut rejected) = (0, 0);
+        for a in 0..101u8 {
+            for tag in [0x02u8, 0x03] {
+                if let Some(q) = G2P::decompress(f101(a as u64), tag == 0x03) {
+                    assert!(q.in_curve());
+                    match G2P::try_from(&[tag, a][..]) {
+                        Ok(_) => in_subgroup += 1,
+                        Err(DecodeError::NotInSubgroup) => rejected += 1,
+                        Err(e) => assert_eq!(e, DecodeError::NotCanonical),
+                    }
+                }
+            }
+        }
+        assert_eq!(in_subgroup, 16);
+        assert_eq!(in_subgroup + rejected, 101);
+
+        // (48,0) is in the twist, and 0 is even
+        assert_eq!(
+            G2P::decompress(f101(48), false),
+            Some(G2P::new(f101(48), f101(0)))
+        );
+        assert_eq!(G2P::decompress(f101(48), true), None);
+        assert_eq!(
+            G2P::try_from(&[0x03u8, 48][..]),
+            Err(DecodeError::NotCanonical)
+        );
+
+        assert_eq!(
+            G2P::try_from(&[0x02u8][..]),
+            Err(DecodeError::InvalidLength {
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            G2P::try_from(&[0x04u8, 36][..]),
+            Err(DecodeError::NotCanonical)
+        );
+        assert_eq!(
+            G2P::try_from(&[0x02u8, 101][..]),
+            Err(DecodeError::NotCanonical)
+        );
+    }
 }
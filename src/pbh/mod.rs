@@ -3,7 +3,13 @@ pub mod g2;
 pub mod gt;
 pub mod pairing;
 
-use crate::{ec::Field, plonk::PlonkTypes, utils::U64Field};
+use std::convert::{TryFrom, TryInto};
+
+use crate::{
+    ec::{Curve, DecodeError, Field, G1Point, Pairing},
+    plonk::{PlonkTypes, SRS},
+    utils::U64Field,
+};
 
 pub type F101 = U64Field<101>;
 pub const fn f101(x: u64) -> F101 {
@@ -15,6 +21,18 @@ pub const fn f17(x: u64) -> F17 {
     U64Field::<17>(x % 17)
 }
 
+/// The PLONK by hand curve $y^2=x^3+3$ over $\mathbb{F}_{101}$, with a G1 subgroup of order 17
+pub struct PlonkByHandCurve {}
+impl Curve for PlonkByHandCurve {
+    type Fq = F101;
+    type G1 = g1::G1P;
+    type G2 = g2::G2P;
+    type GT = gt::GTP;
+    fn pairing(p: &Self::G1, q: &Self::G2) -> Self::GT {
+        pairing::PBHPairing::pairing(*p, *q)
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct PlonkByHandTypes {}
 impl PlonkTypes for PlonkByHandTypes {
@@ -32,13 +50,63 @@ impl PlonkTypes for PlonkByHandTypes {
     }
 }
 
+impl SRS<PlonkByHandTypes> {
+    /// Serializes the SRS as the number of G1 points as a little-endian u32, followed by
+    /// the compressed G1 points and the compressed `g2_1` and `g2_s`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = (self.g1s.len() as u32).to_le_bytes().to_vec();
+        for g1 in &self.g1s {
+            bytes.extend_from_slice(&g1.to_compressed());
+        }
+        bytes.extend_from_slice(&self.g2_1.to_compressed());
+        bytes.extend_from_slice(&self.g2_s.to_compressed());
+        bytes
+    }
+
+    /// Deserializes the `to_bytes` format, checking that all the points are in the subgroups
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let n = bytes
+            .get(..4)
+            .map(|n| u32::from_le_bytes(n.try_into().unwrap()) as usize)
+            .ok_or(DecodeError::InvalidLength {
+                expected: 4,
+                found: bytes.len(),
+            })?;
+        let expected = 4 + 2 * n + 4;
+        if bytes.len() != expected {
+            return Err(DecodeError::InvalidLength {
+                expected,
+                found: bytes.len(),
+            });
+        }
+        let mut chunks = bytes[4..].chunks(2);
+        let g1s = chunks
+            .by_ref()
+            .take(n)
+            .map(|chunk| {
+                let g1 = g1::G1P::try_from(chunk)?;
+                if g1.in_subgroup() {
+                    Ok(g1)
+                } else {
+                    Err(DecodeError::NotInSubgroup)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let g2_1 = g2::G2P::try_from(chunks.next().unwrap())?;
+        let g2_s = g2::G2P::try_from(chunks.next().unwrap())?;
+        Ok(SRS { g1s, g2_1, g2_s })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         constraints::{Assigment, Assigments, Constrains, CopyOf, Gate},
+        ec::{G1Point, G2Point, GTPoint},
         pbh::g1::g1f,
         plonk::{Challange, Plonk, Proof, SRS},
+        poly::Poly,
     };
 
     #[test]
@@ -50,7 +118,8 @@ mod tests {
         let plonk = Plonk::new(
             srs,
             f17(4), // omega pows
-        );
+        )
+        .unwrap();
 
         // constraints and assigments
         let constraints = Constrains::new(
@@ -122,4 +191,91 @@ mod tests {
         let rand = [f17(4)];
         assert!(plonk.verify(&constraints, &proof, &challange, rand));
     }
+
+    #[test]
+    fn test_srs_commit_and_open() {
+        let srs = SRS::<PlonkByHandTypes>::create(f101(2), 6);
+        let poly = Poly::from(&[1, 13, 3, 3]);
+
+        for z in [f17(5), f17(12)] {
+            let (commitment, y, proof) = srs.commit_and_open(&poly, z);
+            assert_eq!(commitment, srs.eval_at_s(&poly));
+            assert_eq!((y, proof), srs.open(&poly, z));
+            assert_eq!(y, poly.eval(&z));
+            assert!(srs.verify_open(commitment, z, y, proof));
+            assert!(!srs.verify_open(commitment, z, y + f17(1), proof));
+        }
+    }
+
+    #[test]
+    fn test_curve_bilinearity() {
+        type C = PlonkByHandCurve;
+        let p = <C as Curve>::G1::generator();
+        let q = <C as Curve>::G2::generator();
+        let e = |p, q| <C as Curve>::pairing(&p, &q);
+        // scalars are F101 elements, so products are kept below 101 to not reduce them
+        for (a, b) in [(2, 3), (5, 7), (4, 16)] {
+            let (a, b) = (f101(a), f101(b));
+            assert_eq!(e(p * a, q * b), e(p * (a * b), q));
+            assert_eq!(e(p * a, q * b), e(p, q * (a * b)));
+            assert_eq!(e(p * a, q * b), e(p, q).pow((a * b).as_u64()));
+        }
+        assert_eq!(<C as Curve>::G1::generator_subgroup_size(), f101(17));
+        assert_eq!(<C as Curve>::Fq::characteristic(), 101);
+    }
+
+    #[test]
+    fn test_srs_bytes() {
+        let srs = SRS::<PlonkByHandTypes>::create(f101(2), 6);
+        let bytes = srs.to_bytes();
+        assert_eq!(bytes.len(), 4 + 7 * 2 + 2 * 2);
+
+        let loaded = SRS::<PlonkByHandTypes>::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.g1s, srs.g1s);
+        assert_eq!(loaded.g2_1, srs.g2_1);
+        assert_eq!(loaded.g2_s, srs.g2_s);
+
+        // (48,0) is in the curve but not in the order 17 subgroup
+        let mut corrupted = bytes.clone();
+        corrupted[6..8].copy_from_slice(&g1f(48, 0).to_compressed());
+        assert_eq!(
+            SRS::<PlonkByHandTypes>::from_bytes(&corrupted).err(),
+            Some(DecodeError::NotInSubgroup)
+        );
+        // x=0 is not in the curve
+        let mut corrupted = bytes.clone();
+        corrupted[5] = 0;
+        assert_eq!(
+            SRS::<PlonkByHandTypes>::from_bytes(&corrupted).err(),
+            Some(DecodeError::NotInCurve)
+        );
+        assert!(SRS::<PlonkByHandTypes>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(SRS::<PlonkByHandTypes>::from_bytes(&bytes[..3]).is_err());
+    }
+
+    #[test]
+    fn test_plonk_subgroup_bounds() {
+        // the pairing needs the G1 subgroup order to divide p^k-1
+        let p = F101::order();
+        let r = <PlonkByHandTypes as PlonkTypes>::G1::generator_subgroup_size().as_u64();
+        let k = <PlonkByHandTypes as PlonkTypes>::G2::embeeding_degree();
+        assert_eq!((p.pow(k as u32) - 1) % r, 0);
+
+        assert_eq!(F17::characteristic(), r);
+        assert_eq!(PlonkByHandTypes::max_circuit_size(), 4);
+
+        let srs = || SRS::<PlonkByHandTypes>::create(f101(2), 6);
+        for (n, msg) in [
+            (8, "circuit size 8 is not the order 4 of omega 4"),
+            // 4^5 = 4, so H would be [1,4,16,13,1]
+            (5, "circuit size 5 is not the order 4 of omega 4"),
+            (3, "circuit size 3 is not the order 4 of omega 4"),
+            // 4^0 = 1, but H would be empty
+            (0, "circuit size 0 is not the order 4 of omega 4"),
+        ] {
+            let err = Plonk::new(srs(), f17(n)).err().unwrap();
+            assert_eq!(err.to_string(), msg);
+        }
+        assert!(Plonk::new(srs(), f17(4)).is_ok());
+    }
 }
@@ -4,6 +4,7 @@ use crate::{
     ec::{GTPoint, Pairing},
     poly::Field,
 };
+use anyhow::{anyhow, Result};
 
 use super::{
     constraints::*,
@@ -23,6 +24,19 @@ pub trait PlonkTypes: PartialEq {
     const K2: Self::HF; // <K2 x OMEGA> coset generator
     const OMEGA: Self::HF; // The generator in HF
     fn gf(sf: Self::HF) -> Self::GF;
+
+    /// Returns the maximum number of gates that a circuit can have, that is the
+    /// multiplicative order of `OMEGA`, since $H$ is generated by it, panics if `OMEGA` is zero
+    fn max_circuit_size() -> usize {
+        assert!(!Self::OMEGA.is_zero(), "OMEGA cannot be zero");
+        let mut order = 1;
+        let mut omega_pow = Self::OMEGA;
+        while omega_pow != Self::HF::one() {
+            omega_pow = omega_pow * Self::OMEGA;
+            order += 1;
+        }
+        order
+    }
 }
 
 pub struct SRS<P: PlonkTypes> {
@@ -56,6 +70,29 @@ impl<P: PlonkTypes> SRS<P> {
                 acc + self.g1s[n] * P::gf(*v)
             })
     }
+
+    /// Opens `poly` at `z`, returning $p(z)$ and the proof $[q(s)]$ where $q(x)=\frac{p(x)-p(z)}{x-z}$
+    pub fn open(&self, poly: &Poly<P::HF>, z: P::HF) -> (P::HF, P::G1) {
+        let (q, y) = poly.divide_by_linear(z);
+        (y, self.eval_at_s(&q))
+    }
+
+    /// Commits to `poly` and opens it at `z`, returning the commitment $[p(s)]$, $p(z)$ and the proof
+    pub fn commit_and_open(&self, poly: &Poly<P::HF>, z: P::HF) -> (P::G1, P::HF, P::G1) {
+        let (y, proof) = self.open(poly, z);
+        (self.eval_at_s(poly), y, proof)
+    }
+
+    /// Checks that `proof` opens `commitment` to `y` at `z`, with the same G1-only
+    /// arrangement than the verifier $e([q(s)], [s]_2) = e(z[q(s)] + [p(s)] - y[1], [1]_2)$
+    pub fn verify_open(&self, commitment: P::G1, z: P::HF, y: P::HF, proof: P::G1) -> bool {
+        let e_1 = P::E::pairing(proof, self.g2_s);
+        let e_2 = P::E::pairing(
+            proof * P::gf(z) + commitment + -(self.g1s[0] * P::gf(y)),
+            self.g2_1,
+        );
+        e_1 == e_2
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -117,7 +154,20 @@ pub struct Plonk<P: PlonkTypes> {
 }
 
 impl<P: PlonkTypes> Plonk<P> {
-    pub fn new(srs: SRS<P>, omega_pows: P::HF) -> Self {
+    pub fn new(srs: SRS<P>, omega_pows: P::HF) -> Result<Self> {
+        // H must be the whole subgroup generated by omega, without repeated elements,
+        // so the circuit size is the order of omega, check it before doing any computation
+
+        let circuit_size = omega_pows.as_u64() as usize;
+        if circuit_size != P::max_circuit_size() {
+            return Err(anyhow!(
+                "circuit size {} is not the order {} of omega {}",
+                circuit_size,
+                P::max_circuit_size(),
+                P::OMEGA
+            ));
+        }
+
         // This roots of unity should be able to be generated through a generator
         // So the generator (called omega) creates these roots of unity (H)
 
@@ -130,12 +180,15 @@ impl<P: PlonkTypes> Plonk<P> {
         // neither an element of H nor $k_1H$. This ensures we have all the field elements to use as labels.
         // $H$ will be used to index $a$ values, $k_1H$ for $b$ values, $k_2H$ for $cC values
 
-        assert!(!h.contains(&P::K1));
-        assert!(!h.contains(&P::K2));
+        if h.contains(&P::K1) || h.contains(&P::K2) {
+            return Err(anyhow!("k1 and k2 must not be in H"));
+        }
 
         let k1_h: Vec<_> = h.iter().map(|r| *r * P::K1).collect(); // k1_h is a coset of H
 
-        assert!(!k1_h.contains(&P::K2));
+        if k1_h.contains(&P::K2) {
+            return Err(anyhow!("k2 must not be in k1H"));
+        }
         let k2_h: Vec<_> = h.iter().map(|r| *r * P::K2).collect(); // k2_h is a coset of H
 
         // In some point we will want to build polinomials that evaluates with specific values
@@ -164,14 +217,14 @@ impl<P: PlonkTypes> Plonk<P> {
 
         let z_h_x = Poly::z(&h);
 
-        Plonk {
+        Ok(Plonk {
             srs,
             h,
             h_pows_inv,
             k1_h,
             k2_h,
             z_h_x,
-        }
+        })
     }
 
     fn interpolate_at_h(&self, vv: &[P::HF]) -> Poly<P::HF> {
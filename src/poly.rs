@@ -1,16 +1,38 @@
 pub use crate::ec::Field;
+use crate::fft::{mul_ntt, root_of_unity, CooleyTurkey, EvaluationDomainGenerator, FFT};
 pub use crate::matrix::Matrix;
 pub use anyhow::anyhow;
 use std::convert::TryFrom;
 use std::{
     cmp::max,
     fmt::{Display, Formatter},
-    ops::{Add, AddAssign, Div, Mul, MulAssign, Sub, SubAssign},
+    ops::{Add, AddAssign, Div, Index, Mul, MulAssign, Neg, Sub, SubAssign},
 };
 
+/// Minimum degree of both operands to multiply them with NTT instead of schoolbook,
+/// measured with `bench_poly_mul_ntt_threshold`, both take the same time around 256
+/// and NTT is already twice as fast at 512
+const NTT_MUL_THRESHOLD: usize = 256;
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Poly<F: Field>(Vec<F>);
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolyError {
+    /// The divisor is the zero polinomial
+    DivideByZero,
+}
+
+impl Display for PolyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolyError::DivideByZero => write!(f, "polinomial division by zero"),
+        }
+    }
+}
+
+impl std::error::Error for PolyError {}
+
 impl<F: Field> Poly<F> {
     /// Creates a new Poly from its `coeffs`icients, first element the coefficient for x^0
     /// for safetly, input value is normalized (trailing zeroes are removed)
@@ -20,16 +42,42 @@ impl<F: Field> Poly<F> {
         poly
     }
 
-    /// Creates a new polinomial where the `coeffs` fits in u64 values
-    pub fn from(coeffs: &[i64]) -> Self {
-        Poly::new(coeffs.iter().map(|n| F::from(*n)).collect::<Vec<F>>())
-    }
     pub fn coeffs(&self) -> &[F] {
         &self.0
     }
     pub fn into_coeffs(self) -> Vec<F> {
         self.0
     }
+    /// Returns the coefficients zero-extended to `n` elements, panics if `n` is less than
+    /// the number of coefficients
+    pub fn coeffs_padded(&self, n: usize) -> Vec<F> {
+        assert!(
+            n >= self.0.len(),
+            "cannot pad {} coefficients to {}",
+            self.0.len(),
+            n
+        );
+        let mut coeffs = self.0.clone();
+        coeffs.resize(n, F::zero());
+        coeffs
+    }
+    /// Iterates the non-zero coefficients with the power of x they belong to
+    pub fn iter_coeffs(&self) -> impl Iterator<Item = (usize, &F)> {
+        self.0.iter().enumerate().filter(|(_, c)| !c.is_zero())
+    }
+    /// Returns a polinomial with the coefficients up to x^`degree`
+    pub fn truncate(&self, degree: usize) -> Self {
+        Poly::new(self.0.iter().take(degree + 1).copied().collect())
+    }
+    /// Returns the polinomial multiplied by x^`k`, prepending `k` zero coefficients
+    pub fn shift_degree(&self, k: usize) -> Self {
+        if self.is_zero() {
+            return Poly::zero();
+        }
+        let mut coeffs = vec![F::zero(); k + self.0.len()];
+        coeffs[k..].copy_from_slice(&self.0);
+        Poly(coeffs)
+    }
     /// Returns p(x)=0
     pub fn zero() -> Self {
         Poly(vec![F::zero()])
@@ -60,6 +108,85 @@ impl<F: Field> Poly<F> {
         l
     }
 
+    /// Creates the polinomial that takes the `evals` values over the `domain` elements,
+    /// using the inverse FFT instead of the general interpolation of `lagrange`
+    pub fn from_evaluations(domain: &EvaluationDomainGenerator<F>, evals: &[F]) -> Self {
+        let mut coeffs = evals.to_vec();
+        domain.ifft(&mut coeffs);
+        Poly::new(coeffs)
+    }
+
+    /// Evaluates the polinomial over the coset `offset`·`domain` with a coset FFT, the
+    /// polinomial must have less coefficients than the domain size and `offset` cannot be zero
+    pub fn eval_coset(&self, offset: F, domain: &EvaluationDomainGenerator<F>) -> Vec<F> {
+        assert!(!offset.is_zero(), "coset offset cannot be zero");
+        // p(offset·x) has coefficients c_i·offset^i
+        let mut offset_pow = F::one();
+        let mut coeffs = self.coeffs_padded(domain.size());
+        for c in coeffs.iter_mut() {
+            *c = *c * offset_pow;
+            offset_pow = offset_pow * offset;
+        }
+        domain.fft(&mut coeffs);
+        coeffs
+    }
+
+    /// Creates the polinomial that takes the `evals` values over the coset `offset`·`domain`,
+    /// the inverse of `eval_coset`, panics if `offset` is zero
+    pub fn interpolate_coset(
+        offset: F,
+        domain: &EvaluationDomainGenerator<F>,
+        evals: &[F],
+    ) -> Self {
+        let offset_inv = offset.inv().expect("coset offset cannot be zero");
+        let mut offset_inv_pow = F::one();
+        let mut coeffs = evals.to_vec();
+        domain.ifft(&mut coeffs);
+        for c in coeffs.iter_mut() {
+            *c = *c * offset_inv_pow;
+            offset_inv_pow = offset_inv_pow * offset_inv;
+        }
+        Poly::new(coeffs)
+    }
+
+    /// Creates a random polinomial of exactly `degree`, taking the coefficients from the
+    /// `rng` u64 values reduced into the field, and skipping zeroes for the leading one
+    pub fn random<R: FnMut() -> u64>(degree: usize, rng: &mut R) -> Self {
+        let mut coeffs: Vec<F> = (0..degree).map(|_| F::from(rng())).collect();
+        let lead = std::iter::repeat_with(|| F::from(rng()))
+            .find(|c| !c.is_zero())
+            .unwrap();
+        coeffs.push(lead);
+        Poly::new(coeffs)
+    }
+
+    /// Creates a polinomial that contains a set of `pairs` points, by using the newton form,
+    /// points are consumed one at time, so they can be generated lazily
+    /// see <https://en.wikipedia.org/wiki/Newton_polynomial>
+    pub fn interpolate<I: IntoIterator<Item = (F, F)>>(pairs: I) -> anyhow::Result<Self> {
+        // p(x) interpolates the points added so far, and n(x) is (x-x_0)...(x-x_k)
+        let mut p = Poly::zero();
+        let mut n = Poly::one();
+        for (x, y) in pairs {
+            let c = ((y - p.eval(&x)) / n.eval(&x)).ok_or_else(|| {
+                anyhow!("interpolation x points must be unique, {} is repeated", x)
+            })?;
+            p += &(&n * c);
+            n = n * Poly::new(vec![-x, F::one()]);
+        }
+        Ok(p)
+    }
+
+    /// Returns if the `evals` over the `domain` points are the evaluations of a polinomial
+    /// of degree at most `bound`, checking directly the degree of the interpolated polinomial
+    /// instead of the probabilistic FRI test
+    pub fn is_low_degree(evals: &[F], domain: &[F], bound: usize) -> bool {
+        assert_eq!(evals.len(), domain.len(), "one evaluation per domain point");
+        Self::interpolate(domain.iter().copied().zip(evals.iter().copied()))
+            .map(|p| p.degree() <= bound)
+            .unwrap_or(false)
+    }
+
     /// Creates a polinomial that has roots at the selected points (x-p_1)(x-p_2)...(x-p_n)
     pub fn z(points: &[F]) -> Self {
         points
@@ -78,15 +205,225 @@ impl<F: Field> Poly<F> {
         y
     }
 
+    /// Evals the polinomial at a point `x` of an extension of the field, using horner
+    pub fn eval_ext<E>(&self, x: E) -> E
+    where
+        E: From<F> + Copy + Add<Output = E> + Mul<Output = E>,
+    {
+        self.0
+            .iter()
+            .rev()
+            .skip(1)
+            .fold(E::from(self.0[self.0.len() - 1]), |acc, c| {
+                acc * x + E::from(*c)
+            })
+    }
+
     /// Evals the polinomial suplying the `x_pows` x^0, x^1, x^2
     pub fn eval_with_pows(&self, x_pow: &[F]) -> F {
-        let mut y = self.0[0];
+        let mut y = F::zero();
         for (i, _) in self.0.iter().enumerate() {
             y += x_pow[i] * self.0[i];
         }
         y
     }
 
+    /// Evals all the `polys` at `x`, computing the powers of `x` only once
+    pub fn eval_all_at(polys: &[&Poly<F>], x: F) -> Vec<F> {
+        let max_len = polys.iter().map(|p| p.0.len()).max().unwrap_or(0);
+        let mut x_pows = Vec::with_capacity(max_len);
+        let mut x_pow = F::one();
+        for _ in 0..max_len {
+            x_pows.push(x_pow);
+            x_pow *= &x;
+        }
+        polys.iter().map(|p| p.eval_with_pows(&x_pows)).collect()
+    }
+
+    /// Evals the linear combination $\sum c_i p_i(x)$ of `polys` with `coeffs` at
+    /// each one of the `points`, evaluating each polinomial instead of building the combination
+    pub fn eval_combination(polys: &[Poly<F>], coeffs: &[F], points: &[F]) -> Vec<F> {
+        assert_eq!(
+            polys.len(),
+            coeffs.len(),
+            "one coefficient per polinomial is required"
+        );
+        let mut evals = vec![F::zero(); points.len()];
+        for (p, c) in polys.iter().zip(coeffs) {
+            for (y, x) in evals.iter_mut().zip(points) {
+                *y += p.eval(x) * c;
+            }
+        }
+        evals
+    }
+
+    /// Returns the linear combination $\sum c_i p_i(x)$ of `polys` with `coeffs`,
+    /// the zero polinomial if there are no polinomials
+    pub fn linear_combination(polys: &[Poly<F>], coeffs: &[F]) -> Self {
+        assert_eq!(
+            polys.len(),
+            coeffs.len(),
+            "one coefficient per polinomial is required"
+        );
+        polys.iter().zip(coeffs).map(|(p, c)| p * c).sum()
+    }
+
+    /// Reduces the polinomial modulo $x^n+1$ (negacyclic reduction), using that $x^n=-1$
+    pub fn mod_cyclotomic(&self, n: usize) -> Self {
+        assert!(n > 0, "cannot reduce modulo x^0+1");
+        let mut coeffs = vec![F::zero(); n];
+        for (i, c) in self.0.iter().enumerate() {
+            if (i / n).is_multiple_of(2) {
+                coeffs[i % n] += c;
+            } else {
+                coeffs[i % n] -= c;
+            }
+        }
+        Poly::new(coeffs)
+    }
+
+    /// Reduces the polinomial modulo $x^n-1$ (cyclic reduction), using that $x^n=1$
+    pub fn mod_x_n_minus_1(&self, n: usize) -> Self {
+        assert!(n > 0, "cannot reduce modulo x^0-1");
+        let mut coeffs = vec![F::zero(); n];
+        for (i, c) in self.0.iter().enumerate() {
+            coeffs[i % n] += c;
+        }
+        Poly::new(coeffs)
+    }
+
+    /// Divides by `rhs`, returning the quotient and the remainder
+    pub fn div_rem(self, rhs: &Poly<F>) -> Result<(Poly<F>, Poly<F>), PolyError> {
+        if rhs.0.iter().all(|c| c.is_zero()) {
+            return Err(PolyError::DivideByZero);
+        }
+        let (mut q, mut r) = (Poly::zero(), self);
+        while !r.is_zero() && r.degree() >= rhs.degree() {
+            let lead_r = r.0[r.0.len() - 1];
+            let lead_d = rhs.0[rhs.0.len() - 1];
+            let mut t = Poly::zero();
+            t.set(r.0.len() - rhs.0.len(), lead_r * lead_d.inv().unwrap());
+            q += &t;
+            r -= &(rhs * &t);
+        }
+        q.normalize();
+        r.normalize();
+        Ok((q, r))
+    }
+
+    /// Like `div_rem`, but panics when dividing by zero, it is what `/` uses
+    pub fn div_rem_unchecked(self, rhs: &Poly<F>) -> (Poly<F>, Poly<F>) {
+        self.div_rem(rhs).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Divides by $(x-z)$ with synthetic division, returning the quotient and the
+    /// remainder, that is $p(z)$
+    pub fn divide_by_linear(&self, z: F) -> (Poly<F>, F) {
+        let mut quotient = vec![F::zero(); self.0.len().max(2) - 1];
+        let mut acc = F::zero();
+        for (n, c) in self.0.iter().enumerate().rev() {
+            acc = acc * z + c;
+            if n > 0 {
+                quotient[n - 1] = acc;
+            }
+        }
+        (Poly::new(quotient), acc)
+    }
+
+    /// Divides by a sparse polinomial returning (quotient, remainder), each step only
+    /// subtracts the non-zero terms of `rhs`, so it takes $O(n \cdot terms)$
+    pub fn div_rem_sparse(self, rhs: &SparsePoly<F>) -> Result<(Poly<F>, Poly<F>), PolyError> {
+        let (lead_n, lead) = rhs.0.last().ok_or(PolyError::DivideByZero)?;
+        let lead_inv = lead.inv().unwrap();
+        let mut r = self.0;
+        if r.len() <= *lead_n {
+            return Ok((Poly::zero(), Poly::new(r)));
+        }
+        let mut q = vec![F::zero(); r.len() - lead_n];
+        for i in (*lead_n..r.len()).rev() {
+            let c = r[i] * lead_inv;
+            if c.is_zero() {
+                continue;
+            }
+            q[i - lead_n] = c;
+            for (n, t) in &rhs.0 {
+                r[i - lead_n + n] -= &(c * t);
+            }
+        }
+        r.truncate((*lead_n).max(1));
+        Ok((Poly::new(q), Poly::new(r)))
+    }
+
+    /// Returns the polinomial raised to `exp`, by square and multiply
+    pub fn pow(&self, mut exp: u64) -> Self {
+        let mut result = Poly::one();
+        let mut base = self.clone();
+        while exp > 0 {
+            if exp % 2 == 1 {
+                result = &result * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    /// Returns the polinomial raised to `exp` modulo `modulus`, by square and multiply
+    /// reducing after each step, so the intermediate degrees stay below twice the modulus one
+    pub fn pow_mod(&self, mut exp: u64, modulus: &Poly<F>) -> Result<Self, PolyError> {
+        let reduce = |p: Poly<F>| p.div_rem(modulus).map(|(_, r)| r);
+        let mut result = reduce(Poly::one())?;
+        let mut base = reduce(self.clone())?;
+        while exp > 0 {
+            if exp % 2 == 1 {
+                result = reduce(&result * &base)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = reduce(&base * &base)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Returns the formal derivative of the polinomial
+    pub fn derivative(&self) -> Self {
+        if self.0.len() == 1 {
+            return Poly::zero();
+        }
+        Poly::new(
+            self.0
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(n, c)| F::from(n as u64) * c)
+                .collect(),
+        )
+    }
+
+    fn mul_schoolbook(&self, rhs: &Poly<F>) -> Poly<F> {
+        let mut mul = vec![F::zero(); self.0.len() + rhs.0.len()];
+        for n in 0..self.0.len() {
+            for m in 0..rhs.0.len() {
+                mul[n + m] += self.0[n] * rhs.0[m];
+            }
+        }
+        Poly::new(mul)
+    }
+
+    // returns None if the field does not have a large enough power of two root of unity
+    fn mul_ntt(&self, rhs: &Poly<F>) -> Option<Poly<F>> {
+        let half = self.0.len().max(rhs.0.len()).next_power_of_two();
+        let omega = root_of_unity(2 * half)?;
+        let (mut a, mut b) = (self.0.clone(), rhs.0.clone());
+        a.resize(half, F::zero());
+        b.resize(half, F::zero());
+        let fft = CooleyTurkey::new(EvaluationDomainGenerator::new(omega, 2 * half));
+        Some(Poly::new(mul_ntt(fft, a, b)))
+    }
+
     /// Returns the degree of the polinominal, degree(x+1) = 1
     pub fn degree(&self) -> usize {
         self.0.len() - 1
@@ -109,6 +446,14 @@ impl<F: Field> Poly<F> {
         self.0.len() == 1 && self.0[0].is_zero()
     }
 
+    /// Returns if both polinomials take the same values over the `domain` points. Unlike
+    /// `==`, that compares the (always normalized) coefficients, different polinomials can
+    /// be semantically equal, e.g. $x^n$ and $1$ over the $n$-th roots of unity, so only
+    /// implies equality if the domain has more points than the degree of both polinomials
+    pub fn semantically_eq(&self, other: &Self, domain: &[F]) -> bool {
+        domain.iter().all(|x| self.eval(x) == other.eval(x))
+    }
+
     /// Sets the `i`-th coefficient to the selected `p` value
     pub fn set(&mut self, i: usize, p: F) {
         if self.0.len() < i + 1 {
@@ -161,6 +506,65 @@ impl<F: Field> TryFrom<Matrix<F>> for Poly<F> {
     }
 }
 
+/// Creates a new polinomial where the `coeffs` fits in i64 values, negative
+/// values are reduced into the field, so `[-1, 0, 1]` is $x^2-1$
+impl<F: Field> From<&[i64]> for Poly<F> {
+    fn from(coeffs: &[i64]) -> Self {
+        Poly::new(coeffs.iter().map(|n| F::from(*n)).collect::<Vec<F>>())
+    }
+}
+
+impl<F: Field, const N: usize> From<&[i64; N]> for Poly<F> {
+    fn from(coeffs: &[i64; N]) -> Self {
+        Poly::from(&coeffs[..])
+    }
+}
+
+impl<F: Field, const N: usize> From<[i64; N]> for Poly<F> {
+    fn from(coeffs: [i64; N]) -> Self {
+        Poly::from(&coeffs[..])
+    }
+}
+
+impl<F: Field> From<Vec<i64>> for Poly<F> {
+    fn from(coeffs: Vec<i64>) -> Self {
+        Poly::from(&coeffs[..])
+    }
+}
+
+impl<F: Field> std::iter::Sum for Poly<F> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Poly::zero(), |acc, p| acc + p)
+    }
+}
+
+impl<F: Field> std::iter::Product for Poly<F> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Poly::one(), |acc, p| acc * p)
+    }
+}
+
+impl<F: Field> AsRef<[F]> for Poly<F> {
+    fn as_ref(&self) -> &[F] {
+        &self.0
+    }
+}
+
+impl<'a, F: Field> IntoIterator for &'a Poly<F> {
+    type Item = &'a F;
+    type IntoIter = std::slice::Iter<'a, F>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<F: Field> Index<usize> for Poly<F> {
+    type Output = F;
+    fn index(&self, n: usize) -> &Self::Output {
+        &self.0[n]
+    }
+}
+
 /* main arithmetics */
 impl<F: Field> AddAssign<&Poly<F>> for Poly<F> {
     fn add_assign(&mut self, rhs: &Poly<F>) {
@@ -193,7 +597,7 @@ impl<F: Field> SubAssign<&Poly<F>> for Poly<F> {
     fn sub_assign(&mut self, rhs: &Poly<F>) {
         for n in 0..max(self.0.len(), rhs.0.len()) {
             if n >= self.0.len() {
-                self.0.push(rhs.0[n]);
+                self.0.push(-rhs.0[n]);
             } else if n < self.0.len() && n < rhs.0.len() {
                 self.0[n] -= &rhs.0[n];
             }
@@ -205,15 +609,12 @@ impl<F: Field> SubAssign<&Poly<F>> for Poly<F> {
 impl<F: Field> Mul<&Poly<F>> for &Poly<F> {
     type Output = Poly<F>;
     fn mul(self, rhs: &Poly<F>) -> Self::Output {
-        let mut mul = vec![F::zero(); self.0.len() + rhs.0.len()];
-        for n in 0..self.0.len() {
-            for m in 0..rhs.0.len() {
-                mul[n + m] += self.0[n] * rhs.0[m];
+        if self.degree().min(rhs.degree()) >= NTT_MUL_THRESHOLD {
+            if let Some(m) = self.mul_ntt(rhs) {
+                return m;
             }
         }
-        let mut m = Poly(mul);
-        m.normalize();
-        m
+        self.mul_schoolbook(rhs)
     }
 }
 
@@ -231,18 +632,7 @@ impl<F: Field> Div for Poly<F> {
     type Output = (Poly<F>, Poly<F>);
 
     fn div(self, rhs: Poly<F>) -> Self::Output {
-        let (mut q, mut r) = (Poly::zero(), self);
-        while !r.is_zero() && r.degree() >= rhs.degree() {
-            let lead_r = r.0[r.0.len() - 1];
-            let lead_d = rhs.0[rhs.0.len() - 1];
-            let mut t = Poly::zero();
-            t.set(r.0.len() - rhs.0.len(), lead_r * lead_d.inv().unwrap());
-            q += &t;
-            r -= &(&rhs * &t);
-        }
-        q.normalize();
-        r.normalize();
-        (q, r)
+        self.div_rem_unchecked(&rhs)
     }
 }
 
@@ -322,6 +712,64 @@ impl<F: Field> Sub<Poly<F>> for Poly<F> {
     }
 }
 
+impl<F: Field> Sub<Poly<F>> for &Poly<F> {
+    type Output = Poly<F>;
+    fn sub(self, rhs: Poly<F>) -> Self::Output {
+        let mut v = self.clone();
+        v -= &rhs;
+        v
+    }
+}
+
+impl<F: Field> Sub<&Poly<F>> for Poly<F> {
+    type Output = Poly<F>;
+    fn sub(mut self, rhs: &Poly<F>) -> Self::Output {
+        self -= rhs;
+        self
+    }
+}
+
+impl<F: Field> Sub<&Poly<F>> for &Poly<F> {
+    type Output = Poly<F>;
+    fn sub(self, rhs: &Poly<F>) -> Self::Output {
+        let mut v = self.clone();
+        v -= rhs;
+        v
+    }
+}
+
+impl<F: Field> Sub<&F> for Poly<F> {
+    type Output = Poly<F>;
+    fn sub(mut self, rhs: &F) -> Self::Output {
+        self -= rhs;
+        self
+    }
+}
+
+impl<F: Field> Sub<F> for &Poly<F> {
+    type Output = Poly<F>;
+    fn sub(self, rhs: F) -> Self::Output {
+        let mut cloned = self.clone();
+        cloned -= &rhs;
+        cloned
+    }
+}
+
+impl<F: Field> Neg for Poly<F> {
+    type Output = Poly<F>;
+    fn neg(mut self) -> Self::Output {
+        self.0.iter_mut().for_each(|v| *v = -*v);
+        self
+    }
+}
+
+impl<F: Field> Neg for &Poly<F> {
+    type Output = Poly<F>;
+    fn neg(self) -> Self::Output {
+        -self.clone()
+    }
+}
+
 impl<F: Field> Mul<&Poly<F>> for Poly<F> {
     type Output = Poly<F>;
     fn mul(self, rhs: &Poly<F>) -> Self::Output {
@@ -375,26 +823,178 @@ impl<F: Field> Mul<F> for &Poly<F> {
     }
 }
 
-struct PolyFreq<F: Field>(Vec<F>);
+/// A polinomial stored as its non-zero `(power, coefficient)` terms, useful for
+/// polinomials like $x^n-1$ that would need a huge vector of zeroes in `Poly`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparsePoly<F: Field>(Vec<(usize, F)>);
 
-impl<F: Field> Mul for PolyFreq<F> {
-    type Output = PolyFreq<F>;
-    fn mul(self, rhs: PolyFreq<F>) -> Self::Output {
-        let zero = F::zero();
-        let mut c_freq = Vec::new();
+impl<F: Field> SparsePoly<F> {
+    /// Creates a new SparsePoly from its `(power, coefficient)` terms, terms with the same
+    /// power are added, and the result is sorted by power without zero coefficients
+    pub fn new(mut terms: Vec<(usize, F)>) -> Self {
+        terms.sort_by_key(|(n, _)| *n);
+        let mut merged: Vec<(usize, F)> = Vec::with_capacity(terms.len());
+        for (n, c) in terms {
+            match merged.last_mut() {
+                Some((last, acc)) if *last == n => *acc += c,
+                _ => merged.push((n, c)),
+            }
+        }
+        merged.retain(|(_, c)| !c.is_zero());
+        SparsePoly(merged)
+    }
 
-        for n in 0..self.0.len() {
-            let l = self.0.get(n).unwrap_or(&zero);
-            let r = rhs.0.get(n).unwrap_or(&zero);
-            c_freq.push(*l * r);
+    /// Returns $x^n-1$, the polinomial that vanishes in the `n`-th roots of unity
+    pub fn vanishing(n: usize) -> Self {
+        SparsePoly::new(vec![(0, -F::one()), (n, F::one())])
+    }
+
+    /// Returns the non-zero `(power, coefficient)` terms, sorted by power
+    pub fn terms(&self) -> &[(usize, F)] {
+        &self.0
+    }
+
+    /// Returns the degree of the polinomial, zero for p(x)=0
+    pub fn degree(&self) -> usize {
+        self.0.last().map(|(n, _)| *n).unwrap_or(0)
+    }
+
+    /// Returns if p(x)=0
+    pub fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Evals the polinomial at the desired point
+    pub fn eval(&self, x: &F) -> F {
+        self.0
+            .iter()
+            .fold(F::zero(), |acc, (n, c)| acc + x.pow(*n as u64) * c)
+    }
+}
+
+impl<F: Field> From<&Poly<F>> for SparsePoly<F> {
+    fn from(poly: &Poly<F>) -> Self {
+        SparsePoly(poly.iter_coeffs().map(|(n, c)| (n, *c)).collect())
+    }
+}
+
+impl<F: Field> From<&SparsePoly<F>> for Poly<F> {
+    fn from(sparse: &SparsePoly<F>) -> Self {
+        let mut coeffs = vec![F::zero(); sparse.degree() + 1];
+        for (n, c) in &sparse.0 {
+            coeffs[*n] = *c;
+        }
+        Poly::new(coeffs)
+    }
+}
+
+impl<F: Field> Mul<&SparsePoly<F>> for &Poly<F> {
+    type Output = Poly<F>;
+    fn mul(self, rhs: &SparsePoly<F>) -> Self::Output {
+        let mut mul = vec![F::zero(); self.0.len() + rhs.degree()];
+        for (m, b) in &rhs.0 {
+            for (n, a) in self.0.iter().enumerate() {
+                mul[n + m] += *a * b;
+            }
+        }
+        Poly::new(mul)
+    }
+}
+
+impl<F: Field> Mul<&Poly<F>> for &SparsePoly<F> {
+    type Output = Poly<F>;
+    fn mul(self, rhs: &Poly<F>) -> Self::Output {
+        rhs * self
+    }
+}
+
+impl<F: Field> Div<&SparsePoly<F>> for Poly<F> {
+    type Output = (Poly<F>, Poly<F>);
+    fn div(self, rhs: &SparsePoly<F>) -> Self::Output {
+        self.div_rem_sparse(rhs).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl<F: Field> Mul<&SparsePoly<F>> for &SparsePoly<F> {
+    type Output = SparsePoly<F>;
+    fn mul(self, rhs: &SparsePoly<F>) -> Self::Output {
+        let mut terms = Vec::with_capacity(self.0.len() * rhs.0.len());
+        for (n, a) in &self.0 {
+            for (m, b) in &rhs.0 {
+                terms.push((n + m, *a * b));
+            }
+        }
+        SparsePoly::new(terms)
+    }
+}
+
+/// A rational function $num(x)/den(x)$
+#[derive(Clone, Debug, PartialEq)]
+pub struct RationalPoly<F: Field> {
+    pub num: Poly<F>,
+    pub den: Poly<F>,
+}
+
+impl<F: Field> RationalPoly<F> {
+    pub fn new(num: Poly<F>, den: Poly<F>) -> Self {
+        RationalPoly { num, den }
+    }
+
+    /// Evals the rational function at `x`, returns None if `x` is a pole.
+    /// If both numerator and denominator vanish at `x`, L'Hôpital's rule is
+    /// applied until one of the derivatives does not vanish
+    pub fn eval(&self, x: &F) -> Option<F> {
+        let (mut num, mut den) = (self.num.clone(), self.den.clone());
+        loop {
+            if den.is_zero() {
+                return None;
+            }
+            let (n, d) = (num.eval(x), den.eval(x));
+            if !d.is_zero() {
+                return n / d;
+            }
+            if !n.is_zero() {
+                return None;
+            }
+            num = num.derivative();
+            den = den.derivative();
         }
-        Self(c_freq)
+    }
+}
+
+/// Builds the composition polinomial $\sum \alpha_i p_i(x)$ as the constraints are
+/// produced, so only the running sum is kept instead of all the $p_i$
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompositionBuilder<F: Field> {
+    acc: Poly<F>,
+}
+
+impl<F: Field> CompositionBuilder<F> {
+    pub fn new() -> Self {
+        CompositionBuilder { acc: Poly::zero() }
+    }
+
+    /// Adds the constraint `p` multiplied by `alpha` to the running sum
+    pub fn push(&mut self, alpha: F, p: &Poly<F>) {
+        self.acc += &(p * alpha);
+    }
+
+    /// Returns the composition polinomial, the zero polinomial if nothing was pushed
+    pub fn build(self) -> Poly<F> {
+        self.acc
+    }
+}
+
+impl<F: Field> Default for CompositionBuilder<F> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::presets::{stark101, Stark101Field};
     use crate::utils::U64Field;
     type F = U64Field<15485863>;
     type P = Poly<F>;
@@ -423,6 +1023,34 @@ mod tests {
         let mut p003 = P::from(&[1, 2, 3]);
         p003 -= &P::from(&[1, 2]);
         assert_eq!(p003, P::from(&[0, 0, 3]));
+
+        let mut p1_23 = P::from(&[1]);
+        p1_23 -= &P::from(&[0, 2, 3]);
+        assert_eq!(p1_23, P::from(&[1, -2, -3]));
+
+        // results are always normalized, so degree is kept correct
+        let p = P::from(&[1, 2, 3]);
+        assert!((p.clone() - p.clone()).is_zero());
+        assert_eq!((p.clone() - p).coeffs().len(), 1);
+        assert_eq!((P::from(&[1, 0, 1]) - P::from(&[0, 0, 1])).degree(), 0);
+        assert_eq!((P::from(&[1, 0, 1]) - F::one()).degree(), 2);
+        assert_eq!((P::from(&[1]) - F::one()).coeffs().len(), 1);
+        assert_eq!((P::from(&[1, 2, 3]) + P::from(&[0, 0, -3])).degree(), 1);
+    }
+
+    #[test]
+    fn test_poly_neg() {
+        let a = P::from(&[1, 2, 3]);
+        let b = P::from(&[5, 0, 7, 9]);
+        let c = F::from(11u64);
+
+        assert_eq!(-&a, P::from(&[-1, -2, -3]));
+        assert_eq!(-(-a.clone()), a);
+        assert_eq!(-P::zero(), P::zero());
+        assert_eq!(&a - &b, &a + (-&b));
+        assert_eq!(&b - &a, &b + (-&a));
+        assert_eq!(&a - c, &a + (-c));
+        assert!((&a + (-&a)).is_zero());
     }
 
     #[test]
@@ -433,6 +1061,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_poly_pow() {
+        let p = P::from(&[1, 2, 0, 3]);
+        assert_eq!(p.pow(0), P::one());
+        assert_eq!(p.pow(1), p);
+        assert_eq!(p.pow(2), &p * &p);
+        assert_eq!(p.pow(5), &(&(&p * &p) * &(&p * &p)) * &p);
+        for n in 0..8 {
+            assert_eq!(p.pow(n).degree(), n as usize * p.degree());
+        }
+        assert_eq!(P::zero().pow(3), P::zero());
+    }
+
+    #[test]
+    fn test_poly_mul_ntt() {
+        type NttF = U64Field<998244353>;
+        let p = |n: usize, seed: u64| {
+            Poly::<NttF>::new((0..=n as u64).map(|i| NttF::from(i * seed + 1)).collect())
+        };
+        for n in [
+            NTT_MUL_THRESHOLD - 1,
+            NTT_MUL_THRESHOLD,
+            NTT_MUL_THRESHOLD + 1,
+        ] {
+            let (a, b) = (p(n, 7), p(n + 5, 13));
+            let expected = a.mul_schoolbook(&b);
+            assert_eq!(a.mul_ntt(&b), Some(expected.clone()));
+            assert_eq!(&a * &b, expected);
+        }
+
+        // F has no roots of unity large enough, it falls back to schoolbook
+        let a = P::new((0..200u64).map(F::from).collect());
+        assert_eq!(a.mul_ntt(&a), None);
+        assert_eq!(&a * &a, a.mul_schoolbook(&a));
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_poly_mul_ntt_threshold() {
+        // cargo test --release -- --ignored --nocapture bench_poly_mul_ntt_threshold
+        type NttF = U64Field<998244353>;
+        for n in [64, 256, 512, 1024, 2048] {
+            let a = Poly::<NttF>::new((0..n as u64).map(|i| NttF::from(i * 7 + 1)).collect());
+            let start = std::time::Instant::now();
+            for _ in 0..20 {
+                a.mul_schoolbook(&a);
+            }
+            let schoolbook = start.elapsed();
+            let start = std::time::Instant::now();
+            for _ in 0..20 {
+                a.mul_ntt(&a);
+            }
+            println!(
+                "{} schoolbook={:?} ntt={:?}",
+                n,
+                schoolbook,
+                start.elapsed()
+            );
+        }
+    }
+
     #[test]
     fn test_poly_div() {
         fn do_test(n: P, d: P) {
@@ -448,6 +1137,96 @@ mod tests {
         do_test(P::from(&[1, 2, 1, 2, 5, 8, 1, 9]), P::from(&[1, 1, 5, 4]));
     }
 
+    #[test]
+    fn test_poly_mod_x_n() {
+        let x_4 = P::from(&[0, 0, 0, 0, 1]);
+        assert_eq!(x_4.mod_cyclotomic(4), P::from(&[-1]));
+        assert_eq!(x_4.mod_x_n_minus_1(4), P::from(&[1]));
+
+        let low = P::from(&[1, 2, 3]);
+        assert_eq!(low.mod_cyclotomic(4), low);
+        assert_eq!(low.mod_x_n_minus_1(4), low);
+
+        // check against the division remainder
+        let p = P::from(&[1, 2, 1, 2, 5, 8, 1, 9, 3, 4]);
+        assert_eq!(p.mod_cyclotomic(3), (p.clone() / P::from(&[1, 0, 0, 1])).1);
+        assert_eq!(p.mod_x_n_minus_1(3), (p / P::from(&[-1, 0, 0, 1])).1);
+    }
+
+    #[test]
+    fn test_poly_divide_by_linear() {
+        let f = P::from(&[5, -3, 0, 2, 7]);
+        for z in [0u64, 1, 3, 12345].iter().map(|z| F::from(*z)) {
+            let (q, r) = f.divide_by_linear(z);
+            assert_eq!(r, f.eval(&z));
+            assert_eq!(&q * &Poly::new(vec![-z, F::one()]) + r, f);
+            assert_eq!(q, ((&f - r) / Poly::new(vec![-z, F::one()])).0);
+        }
+        assert_eq!(
+            P::from(&[4]).divide_by_linear(F::one()),
+            (P::zero(), F::from(4u64))
+        );
+    }
+
+    #[test]
+    fn test_poly_div_by_zero() {
+        assert_eq!(
+            P::from(&[1, 2, 1]).div_rem(&P::zero()),
+            Err(PolyError::DivideByZero)
+        );
+        assert_eq!(
+            P::from(&[1, 2, 1]).div_rem(&P::from(&[1, 1])),
+            Ok((P::from(&[1, 1]), P::zero()))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "polinomial division by zero")]
+    fn test_poly_div_by_zero_panics() {
+        let _ = P::from(&[1, 2, 1]) / P::zero();
+    }
+
+    #[test]
+    fn test_poly_iter_coeffs() {
+        let p = P::from(&[0, 3, 0, 0, 5, 0, 1]);
+        let coeffs: Vec<_> = p.iter_coeffs().map(|(n, c)| (n, *c)).collect();
+        assert_eq!(
+            coeffs,
+            vec![(1, F::from(3u64)), (4, F::from(5u64)), (6, F::one())]
+        );
+        assert_eq!(P::zero().iter_coeffs().count(), 0);
+    }
+
+    #[test]
+    fn test_poly_shift_degree() {
+        assert_eq!(P::from(&[1, 1]).shift_degree(2), P::from(&[0, 0, 1, 1]));
+        assert_eq!(P::from(&[1, 1]).shift_degree(0), P::from(&[1, 1]));
+        assert_eq!(P::zero().shift_degree(3), P::zero());
+
+        // chunks t(x) = t_lo(x) + x^2 t_mid(x) + x^4 t_hi(x)
+        let t = P::from(&[1, 2, 3, 4, 5, 6]);
+        let chunk = |n: usize| P::new(t.coeffs()[n..].to_vec()).truncate(1);
+        let (t_lo, t_mid, t_hi) = (chunk(0), chunk(2), chunk(4));
+        assert_eq!(t_lo + t_mid.shift_degree(2) + t_hi.shift_degree(4), t);
+    }
+
+    #[test]
+    fn test_poly_truncate() {
+        let p = P::from(&[1, 2, 0, 0, 5, 6]);
+        assert_eq!(p.truncate(1), P::from(&[1, 2]));
+        assert_eq!(p.truncate(3), P::from(&[1, 2]));
+        assert_eq!(p.truncate(5), p);
+        assert_eq!(p.truncate(100), p);
+
+        // p(x) = low(x) + x^4 high(x)
+        let x = F::from(7u64);
+        let high = P::from(&[5, 6]);
+        assert_eq!(
+            p.eval(&x),
+            p.truncate(3).eval(&x) + x.pow(4) * high.eval(&x)
+        );
+    }
+
     #[test]
     fn test_poly_print() {
         assert_eq!("1+2x+x^2", format!("{}", P::from(&[1, 2, 1])));
@@ -467,19 +1246,437 @@ mod tests {
         let l = Poly::lagrange(&points);
         points.iter().for_each(|p| assert_eq!(l.eval(&p.0), p.1));
     }
+    #[test]
+    fn test_poly_interpolate() {
+        let points: Vec<_> = [(1, 2), (5, 7), (7, 9), (3, 1)]
+            .iter()
+            .map(|(x, y)| (F::from(*x as u64), F::from(*y as u64)))
+            .collect();
+        let p = Poly::interpolate(points.iter().copied()).unwrap();
+        assert_eq!(p, Poly::lagrange(&points));
+
+        // x^2-1 from a lazily generated set of points
+        let p = Poly::interpolate((0..3i64).map(|x| (F::from(x), F::from(x * x - 1)))).unwrap();
+        assert_eq!(p, P::from(&[-1, 0, 1]));
+
+        let repeated = [
+            (F::from(1u64), F::from(2u64)),
+            (F::from(1u64), F::from(3u64)),
+        ];
+        assert!(Poly::interpolate(repeated.iter().copied()).is_err());
+    }
+    #[test]
+    fn test_poly_from_evaluations() {
+        type F337 = U64Field<337>;
+        let domain = EvaluationDomainGenerator::new(F337::from(85u64), 8);
+        let evals: Vec<F337> = [3, 1, 4, 1, 5, 9, 2, 6]
+            .iter()
+            .map(|v| F337::from(*v as u64))
+            .collect();
+        let points: Vec<_> = domain
+            .elements()
+            .into_iter()
+            .zip(evals.iter().copied())
+            .collect();
+        assert_eq!(
+            Poly::from_evaluations(&domain, &evals),
+            Poly::lagrange(&points)
+        );
+
+        // a degree 1023 polinomial over the stark101 trace domain
+        let domain = stark101().trace_domain;
+        let f = Poly::new(
+            (0..1024u64)
+                .map(|n| Stark101Field::from(n * n + 7))
+                .collect(),
+        );
+        let evals: Vec<_> = domain.elements().iter().map(|x| f.eval(x)).collect();
+        assert_eq!(Poly::from_evaluations(&domain, &evals), f);
+    }
+
+    #[test]
+    fn test_poly_interpolate_coset() {
+        let stark = stark101();
+        let (h, w) = (stark.eval_domain, stark.generator);
+        let f = Poly::new(
+            (0..1024u64)
+                .map(|n| Stark101Field::from(n * n + 7))
+                .collect(),
+        );
+
+        let f_eval = f.eval_coset(w, &h);
+        assert_eq!(f_eval.len(), 8192);
+        for i in [0usize, 1, 4096, 8191] {
+            assert_eq!(f_eval[i], f.eval(&(w * h.elements()[i])));
+        }
+        assert_eq!(Poly::interpolate_coset(w, &h, &f_eval), f);
+    }
+
+    #[test]
+    #[should_panic(expected = "coset offset cannot be zero")]
+    fn test_poly_eval_coset_zero_offset() {
+        P::one().eval_coset(F::zero(), &EvaluationDomainGenerator::new(F::one(), 1));
+    }
+
+    #[test]
+    fn test_poly_semantically_eq() {
+        let domain = stark101().trace_domain.elements();
+        let one = Poly::<Stark101Field>::one();
+
+        // x^1024-1 as dense and as sparse
+        let mut dense = vec![Stark101Field::zero(); 1025];
+        dense[0] = -Stark101Field::one();
+        dense[1024] = Stark101Field::one();
+        let dense = Poly::new(dense);
+        let sparse: Poly<_> = (&SparsePoly::vanishing(1024)).into();
+        assert_eq!(dense, sparse);
+        assert!(dense.semantically_eq(&sparse, &domain));
+
+        // x^1024 and 1 are the same only over the domain
+        let x_1024 = &dense + &one;
+        assert_ne!(x_1024, one);
+        assert!(x_1024.semantically_eq(&one, &domain));
+        assert!(!x_1024.semantically_eq(&one, &[Stark101Field::from(2u64)]));
+    }
+
+    #[test]
+    fn test_poly_coeffs_padded() {
+        type F337 = U64Field<337>;
+        let p = Poly::<F337>::from(&[3, 1, 4, 1]);
+        let padded = p.coeffs_padded(8);
+        assert_eq!(padded.len(), 8);
+        assert_eq!(&padded[..4], p.coeffs());
+        assert!(padded[4..].iter().all(|c| c.is_zero()));
+        assert_eq!(p.coeffs_padded(4), p.coeffs());
+
+        let domain = EvaluationDomainGenerator::new(F337::from(85u64), 8);
+        let mut evals = padded;
+        domain.fft(&mut evals);
+        assert_eq!(Poly::from_evaluations(&domain, &evals), p);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_poly_coeffs_padded_too_short() {
+        P::from(&[3, 1, 4, 1]).coeffs_padded(3);
+    }
+
+    #[test]
+    fn test_poly_random() {
+        // xorshift64
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut rng = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for degree in 0..20 {
+            assert_eq!(P::random(degree, &mut rng).degree(), degree);
+        }
+
+        // all the F17 elements show up in a similar number
+        let p = Poly::<U64Field<17>>::random(17 * 1000, &mut rng);
+        let mut count = [0usize; 17];
+        for c in p.coeffs() {
+            count[c.as_u64() as usize] += 1;
+        }
+        assert!(count.iter().all(|n| (800..1200).contains(n)), "{:?}", count);
+    }
+
+    #[test]
+    fn test_poly_is_low_degree() {
+        let p = P::from(&[1, 2, 3, 4]);
+        let domain: Vec<F> = (1..=8u64).map(F::from).collect();
+        let mut evals: Vec<F> = domain.iter().map(|x| p.eval(x)).collect();
+        assert!(P::is_low_degree(&evals, &domain, 3));
+        assert!(P::is_low_degree(&evals, &domain, 7));
+        assert!(!P::is_low_degree(&evals, &domain, 2));
+
+        // changing one evaluation adds a multiple of a degree 7 lagrange basis
+        evals[5] += F::one();
+        assert!(!P::is_low_degree(&evals, &domain, 3));
+        assert!(P::is_low_degree(&evals, &domain, 7));
+    }
+
     #[test]
     fn test_poly_z() {
         assert_eq!(
-            P::z(&vec![F::from(1u64), F::from(5u64)]),
+            P::z(&[F::from(1u64), F::from(5u64)]),
             P::from(&[5, -6, 1]) // f(x) = (x-1)(x-5) = x^2-6x+5
         );
     }
     #[test]
+    fn test_poly_from_signed() {
+        let p = P::from(&[-1, 0, 1]);
+        assert_eq!(p, P::new(vec![-F::one(), F::zero(), F::one()]));
+        assert_eq!(p, P::z(&[F::one(), -F::one()]));
+        assert!(p.eval(&F::one()).is_zero());
+
+        // arrays, vecs and slices build the same polinomial
+        assert_eq!(P::from([-1, 0, 1]), p);
+        assert_eq!(P::from(vec![-1, 0, 1]), p);
+        assert_eq!(P::from(&vec![-1, 0, 1][..]), p);
+        assert!(P::from([-1, 0, 1]).eval(&1u64.into()).is_zero());
+        let q: P = [2, 3, 0, 1].into();
+        assert_eq!(
+            q,
+            P::new(vec![2u64.into(), 3u64.into(), F::zero(), F::one()])
+        );
+
+        // values at the edge of the i64 range
+        assert_eq!(
+            P::from(&[i64::MIN, i64::MAX]),
+            P::new(vec![-F::from(1u64 << 63), F::from(i64::MAX as u64)])
+        );
+    }
+    #[test]
     fn test_poly_eval() {
         // check that (x^2+2x+1)(2) = 9
         assert_eq!(P::from(&[1, 2, 1]).eval(&F::from(2u64)), F::from(9u64));
     }
     #[test]
+    fn test_poly_eval_combination() {
+        let polys = [P::from(&[1, 2, 1]), P::from(&[5, 0, 0, 3]), P::from(&[-7])];
+        let coeffs = [F::from(3u64), F::from(11u64), F::from(2u64)];
+        let points: Vec<_> = (0..8u64).map(F::from).collect();
+
+        let combination = polys
+            .iter()
+            .zip(&coeffs)
+            .fold(P::zero(), |acc, (p, c)| acc + p * c);
+        let expected: Vec<_> = points.iter().map(|x| combination.eval(x)).collect();
+
+        assert_eq!(P::eval_combination(&polys, &coeffs, &points), expected);
+    }
+    #[test]
+    fn test_poly_eval_all_at() {
+        let polys = [
+            P::from(&[1, 2, 1]),
+            P::from(&[7]),
+            P::from(&[0, 3, 0, 0, 5, 9]),
+            P::zero(),
+        ];
+        let x = F::from(1234u64);
+        let polys_ref: Vec<_> = polys.iter().collect();
+        let expected: Vec<_> = polys.iter().map(|p| p.eval(&x)).collect();
+        assert_eq!(P::eval_all_at(&polys_ref, x), expected);
+
+        let x_pows = [F::one(), x, x * x];
+        assert_eq!(polys[0].eval_with_pows(&x_pows), polys[0].eval(&x));
+    }
+    #[test]
+    fn test_poly_eval_ext() {
+        // F[u]/(u^2+2)
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        struct Fp2(F, F);
+        impl From<F> for Fp2 {
+            fn from(a: F) -> Self {
+                Fp2(a, F::zero())
+            }
+        }
+        impl Add for Fp2 {
+            type Output = Fp2;
+            fn add(self, rhs: Fp2) -> Fp2 {
+                Fp2(self.0 + rhs.0, self.1 + rhs.1)
+            }
+        }
+        impl Mul for Fp2 {
+            type Output = Fp2;
+            fn mul(self, rhs: Fp2) -> Fp2 {
+                Fp2(
+                    self.0 * rhs.0 - F::from(2u64) * self.1 * rhs.1,
+                    self.0 * rhs.1 + self.1 * rhs.0,
+                )
+            }
+        }
+
+        // p(1+u) = 1 + 2(1+u) + (1+u)^2 = 1 + 2+2u + 1+2u-2 = 2+4u
+        let p = P::from(&[1, 2, 1]);
+        let x = Fp2(F::one(), F::one());
+        assert_eq!(p.eval_ext(x), Fp2(F::from(2u64), F::from(4u64)));
+
+        let x = F::from(7u64);
+        assert_eq!(p.eval_ext(Fp2::from(x)), Fp2::from(p.eval(&x)));
+    }
+    #[test]
+    fn test_poly_linear_combination() {
+        let p0 = P::from(&[1, 2]);
+        let p1 = P::from(&[0, 0, 3]);
+        let p2 = P::from(&[5, 0, 0, 7]);
+        let (a0, a1, a2) = (F::from(2u64), F::from(3u64), F::from(4u64));
+
+        let cp = &p0 * a0 + &p1 * a1 + &p2 * a2;
+        let combination =
+            P::linear_combination(&[p0.clone(), p1.clone(), p2.clone()], &[a0, a1, a2]);
+        assert_eq!(combination, cp);
+        assert_eq!(combination.degree(), 3);
+        assert_eq!(P::linear_combination(&[], &[]), P::zero());
+
+        assert_eq!(
+            vec![p0.clone(), p1.clone(), p2.clone()]
+                .into_iter()
+                .sum::<P>(),
+            &(&p0 + &p1) + &p2
+        );
+        assert_eq!(
+            vec![p0.clone(), p1.clone()].into_iter().product::<P>(),
+            &p0 * &p1
+        );
+        assert_eq!(Vec::<P>::new().into_iter().sum::<P>(), P::zero());
+        assert_eq!(Vec::<P>::new().into_iter().product::<P>(), P::one());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_poly_linear_combination_len_mismatch() {
+        P::linear_combination(&[P::one()], &[]);
+    }
+
+    #[test]
+    fn test_composition_builder() {
+        let domain = stark101().trace_domain.elements();
+        let f = Poly::new(
+            (0..1024u64)
+                .map(|n| Stark101Field::from(n * n + 7))
+                .collect(),
+        );
+        // synthetic constraints, two boundary quotients (f(x)-f(z))/(x-z) and a random one
+        let (p0, _) = (&f - f.eval(&domain[0])).divide_by_linear(domain[0]);
+        let (p1, _) = (&f - f.eval(&domain[1022])).divide_by_linear(domain[1022]);
+        let mut seed = 42u64;
+        let mut rng = || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            seed >> 33
+        };
+        let p2 = Poly::random(1023, &mut rng);
+
+        let alphas: Vec<_> = (1..4u64).map(|n| Stark101Field::from(n * 1000)).collect();
+        let mut builder = CompositionBuilder::new();
+        for (alpha, p) in alphas.iter().zip([&p0, &p1, &p2]) {
+            builder.push(*alpha, p);
+        }
+        let cp = builder.build();
+        assert_eq!(cp, Poly::linear_combination(&[p0, p1, p2], &alphas));
+        assert_eq!(cp.degree(), 1023);
+        assert_eq!(
+            CompositionBuilder::<Stark101Field>::default().build(),
+            Poly::zero()
+        );
+    }
+
+    #[test]
+    fn test_poly_iter() {
+        // trailing zeroes are trimmed, so iteration stops at the degree
+        let p = P::from(&[1, 2, 3, 0, 0]);
+        let coeffs: Vec<_> = (&p).into_iter().copied().collect();
+        assert_eq!(coeffs, P::from(&[1, 2, 3]).into_coeffs());
+        assert_eq!(p.as_ref().len(), p.degree() + 1);
+
+        let mut count = 0;
+        for (n, c) in (&p).into_iter().enumerate() {
+            assert_eq!(c, &p[n]);
+            assert_eq!(c, &p.as_ref()[n]);
+            count += 1;
+        }
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn test_poly_pow_mod() {
+        let x = P::from(&[0, 1]);
+        assert_eq!(x.pow_mod(2, &P::from(&[-1, 0, 1])), Ok(P::one()));
+        assert_eq!(x.pow_mod(0, &P::from(&[-1, 0, 1])), Ok(P::one()));
+        assert_eq!(x.pow_mod(0, &P::one()), Ok(P::zero()));
+
+        let p = P::from(&[3, 1, 4]);
+        let modulus = P::from(&[1, 5, 9, 2, 6]);
+        for exp in [1, 2, 7, 23] {
+            let (_, expected) = p.pow(exp).div_rem(&modulus).unwrap();
+            assert_eq!(p.pow_mod(exp, &modulus), Ok(expected));
+        }
+        assert_eq!(p.pow_mod(3, &P::zero()), Err(PolyError::DivideByZero));
+    }
+
+    #[test]
+    fn test_poly_derivative() {
+        assert_eq!(P::from(&[1, 2, 3]).derivative(), P::from(&[2, 6]));
+        assert_eq!(P::from(&[7]).derivative(), P::zero());
+        assert_eq!(P::zero().derivative(), P::zero());
+    }
+
+    #[test]
+    fn test_rational_poly_eval() {
+        // (x^2-1)/(x-1)
+        let r = RationalPoly::new(P::from(&[-1, 0, 1]), P::from(&[-1, 1]));
+        assert_eq!(r.eval(&F::from(1u64)), Some(F::from(2u64)));
+        assert_eq!(r.eval(&F::from(3u64)), Some(F::from(4u64)));
+
+        // (x-1)^2/(x-1)^2, needs L'Hôpital twice
+        let r = RationalPoly::new(P::from(&[1, -2, 1]), P::from(&[1, -2, 1]));
+        assert_eq!(r.eval(&F::from(1u64)), Some(F::one()));
+
+        // 1/(x-1) and (x-1)/(x-1)^2 have a pole at 1
+        let r = RationalPoly::new(P::one(), P::from(&[-1, 1]));
+        assert_eq!(r.eval(&F::from(1u64)), None);
+        let r = RationalPoly::new(P::from(&[-1, 1]), P::from(&[1, -2, 1]));
+        assert_eq!(r.eval(&F::from(1u64)), None);
+    }
+
+    #[test]
+    fn test_sparse_dense_arithmetic() {
+        type SF = Stark101Field;
+        let z_h = SparsePoly::<SF>::vanishing(1024);
+        let z_h_dense: Poly<SF> = (&z_h).into();
+        let f = Poly::new((0..1100u64).map(|n| SF::from(n * n + 7)).collect());
+        let x = SF::from(31415u64);
+
+        let (q, r) = f.clone() / &z_h;
+        let (q_dense, r_dense) = f.clone() / z_h_dense.clone();
+        assert_eq!((&q, &r), (&q_dense, &r_dense));
+        assert_eq!(q.degree(), 1100 - 1 - 1024);
+        assert!(r.degree() < 1024);
+
+        // exact division, like the p2 constraint over the vanishing polinomial
+        let numer = &f * &z_h;
+        assert_eq!(numer, &f * &z_h_dense);
+        assert_eq!(numer, &z_h * &f);
+        assert_eq!(numer.eval(&x), f.eval(&x) * z_h.eval(&x));
+        let (q, r) = numer / &z_h;
+        assert_eq!(q, f);
+        assert!(r.is_zero());
+
+        // dividing by a sparse constant or by a higher degree
+        let (q, r) = f.clone() / &SparsePoly::new(vec![(0, SF::from(2u64))]);
+        assert_eq!((&q * SF::from(2u64), r), (f.clone(), Poly::zero()));
+        let (q, r) = f.clone() / &SparsePoly::vanishing(4096);
+        assert_eq!((q, r), (Poly::zero(), f.clone()));
+        assert!(f.div_rem_sparse(&SparsePoly::new(vec![])).is_err());
+    }
+
+    #[test]
+    fn test_sparse_poly() {
+        let z_h = SparsePoly::<F>::vanishing(1024);
+        assert_eq!(z_h.terms().len(), 2);
+        assert_eq!(z_h.degree(), 1024);
+
+        let dense: P = (&z_h).into();
+        assert_eq!(dense.degree(), 1024);
+        assert_eq!(SparsePoly::from(&dense), z_h);
+        for x in [0u64, 1, 2, 12345].iter().map(|x| F::from(*x)) {
+            assert_eq!(z_h.eval(&x), dense.eval(&x));
+        }
+
+        // (x^1024-1)(x^1024+1) = x^2048-1
+        let z_h_conj = SparsePoly::new(vec![(1024, F::one()), (0, F::one())]);
+        assert_eq!(&z_h * &z_h_conj, SparsePoly::vanishing(2048));
+
+        // terms are merged and zeroes removed
+        let p = SparsePoly::new(vec![(3, F::one()), (1, F::one()), (3, -F::one())]);
+        assert_eq!(p.terms(), &[(1, F::one())]);
+        assert!(SparsePoly::<F>::new(vec![(5, F::zero())]).is_zero());
+    }
+    #[test]
     fn test_poly_normalize() {
         let mut p1 = P::from(&[1, 0, 0, 0]);
         p1.normalize();
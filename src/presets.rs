@@ -0,0 +1,89 @@
+//! Ready to use configurations of the curves and fields used in the tutorials, so the
+//! generators and domains don't need to be computed by hand
+
+use crate::{
+    ec::{Field, G1Point, G2Point, Pairing},
+    fft::EvaluationDomainGenerator,
+    pbh::{g1::G1P, g2::G2P, gt::GTP, pairing::PBHPairing, F101},
+    utils::U64Field,
+};
+
+/// The $\mathbb{F}_{101}$ base field of `bn_toy`
+pub type BnToyField = F101;
+
+/// The curve $y^2=x^3+3$ over $\mathbb{F}_{101}$ of the PLONK by hand tutorial
+pub struct BnToy {
+    /// Generator $(1,2)$ of the order 17 subgroup of G1
+    pub g1: G1P,
+    /// Generator $(36,31u)$ of G2
+    pub g2: G2P,
+    /// Order of the subgroups generated by `g1` and `g2`
+    pub subgroup_order: u64,
+    /// Embedding degree of the curve
+    pub embedding_degree: u64,
+    /// The pairing $e: G_1 \times G_2 \to G_T$
+    pub pairing: fn(G1P, G2P) -> GTP,
+}
+
+pub fn bn_toy() -> BnToy {
+    BnToy {
+        g1: G1P::generator(),
+        g2: G2P::generator(),
+        subgroup_order: G1P::generator_subgroup_size().as_u64(),
+        embedding_degree: G2P::embeeding_degree(),
+        pairing: PBHPairing::pairing,
+    }
+}
+
+/// The $3 \cdot 2^{30} + 1$ prime field of the stark101 tutorial
+pub type Stark101Field = U64Field<3221225473>;
+
+/// The field and domains of the stark101 tutorial
+pub struct Stark101 {
+    /// Generator of the whole multiplicative group, also used as the coset offset
+    pub generator: Stark101Field,
+    /// The 1024 elements domain where the trace is interpolated
+    pub trace_domain: EvaluationDomainGenerator<Stark101Field>,
+    /// The 8192 elements domain that, shifted by `generator`, is used for the extension
+    pub eval_domain: EvaluationDomainGenerator<Stark101Field>,
+}
+
+pub fn stark101() -> Stark101 {
+    let generator = Stark101Field::from(5u64);
+    Stark101 {
+        generator,
+        trace_domain: EvaluationDomainGenerator::new(generator.pow(3 * (1 << 20)), 1024),
+        eval_domain: EvaluationDomainGenerator::new(generator.pow(3 * (1 << 17)), 8192),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pbh::{f101, g1::g1f};
+
+    #[test]
+    fn test_bn_toy() {
+        let bn = bn_toy();
+        assert_eq!(bn.g1, g1f(1, 2));
+        assert_eq!(bn.g2, G2P::new(f101(36), f101(31)));
+        assert_eq!(bn.subgroup_order, 17);
+        assert_eq!(bn.embedding_degree, 2);
+        assert_eq!(BnToyField::order(), 101);
+        assert_eq!(
+            (bn.pairing)(bn.g1 * f101(2), bn.g2),
+            (bn.pairing)(bn.g1, bn.g2 * f101(2))
+        );
+    }
+
+    #[test]
+    fn test_stark101() {
+        let stark = stark101();
+        let (g, h) = (stark.trace_domain, stark.eval_domain);
+        assert_eq!(g.size(), 1024);
+        assert_eq!(h.size(), 8192);
+        assert_eq!(stark.generator.multiplicative_order(), Some(3221225472));
+        assert_eq!(g.elements()[1].multiplicative_order(), Some(1024));
+        assert_eq!(h.elements()[1].multiplicative_order(), Some(8192));
+    }
+}
@@ -1,10 +1,15 @@
 #![allow(clippy::many_single_char_names)]
 
-use crate::{ec::Field, poly::Poly};
+use crate::{
+    ec::{DecodeError, Field, FieldError},
+    poly::Poly,
+};
 
 use std::{
+    convert::{TryFrom, TryInto},
     fmt::Display,
     ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign},
+    sync::OnceLock,
 };
 
 fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
@@ -24,12 +29,163 @@ fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
     (old_r, old_s, old_t)
 }
 
+/// An element of the prime field of order `M`
+///
+/// The value is always kept as its canonical residue in $[0,M)$, all the constructors and
+/// operations return canonical values, so the derived `Eq` and `Hash` can compare the
+/// inner value directly
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct U64Field<const M: u64>(pub(crate) u64);
 
+// F101 is the base field of the PLONK by hand curve, and point additions invert a lot,
+// so their inverses are precomputed (zero is stored as zero)
+fn f101_inverses() -> &'static [u64; 101] {
+    static INVERSES: OnceLock<[u64; 101]> = OnceLock::new();
+    INVERSES.get_or_init(|| {
+        let mut inverses = [0; 101];
+        for (n, inv) in inverses.iter_mut().enumerate().skip(1) {
+            *inv = U64Field::<101>(n as u64).inv_extended_gcd().unwrap().0;
+        }
+        inverses
+    })
+}
+
+impl<const M: u64> U64Field<M> {
+    /// Returns the canonical residue in $[0,M)$ of the value
+    pub fn normalize(self) -> Self {
+        Self(self.0 % M)
+    }
+
+    /// Creates the element from a value that must be already in $[0,M)$, use it
+    /// instead of `from` to assert that inputs are canonical
+    pub fn from_strict(n: u64) -> Self {
+        assert!(n < M, "value {} >= modulus {}", n, M);
+        Self(n)
+    }
+
+    /// Creates the element reducing a 128 bits value, e.g. a product of two u64
+    pub fn from_u128(n: u128) -> Self {
+        Self((n % M as u128) as u64)
+    }
+
+    // products are computed in u128, so they don't overflow for moduli above 2^32
+    fn mul_mod(a: u64, b: u64) -> u64 {
+        (a as u128 * b as u128 % M as u128) as u64
+    }
+
+    // both operands are below M, so the sum is below 2M and one subtraction reduces it,
+    // the carry covers moduli above 2^63 where the sum does not fit in u64
+    fn add_mod(a: u64, b: u64) -> u64 {
+        let (sum, carry) = a.overflowing_add(b);
+        if carry || sum >= M {
+            sum.wrapping_sub(M)
+        } else {
+            sum
+        }
+    }
+
+    /// Moves the value to the field of order `N`, failing if it is not smaller than `N`
+    pub fn cast<const N: u64>(&self) -> Result<U64Field<N>, FieldError> {
+        if self.0 < N {
+            Ok(U64Field(self.0))
+        } else {
+            Err(FieldError::OutOfRange {
+                value: self.0,
+                modulus: N,
+            })
+        }
+    }
+
+    /// Returns the little-endian encoding of the canonical value, same as `to_bytes_le`
+    pub fn to_bytes(&self) -> [u8; 8] {
+        self.to_bytes_le()
+    }
+
+    /// Returns the little-endian encoding of the canonical value
+    pub fn to_bytes_le(&self) -> [u8; 8] {
+        self.0.to_le_bytes()
+    }
+
+    /// Returns the big-endian encoding of the canonical value, the byte order used by
+    /// Ethereum's alt_bn128
+    pub fn to_bytes_be(&self) -> [u8; 8] {
+        self.0.to_be_bytes()
+    }
+
+    /// Decodes the little-endian canonical encoding, rejecting values not in $[0,M)$
+    pub fn from_bytes_le(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::from_u64_bytes(bytes, u64::from_le_bytes)
+    }
+
+    /// Decodes the big-endian canonical encoding, rejecting values not in $[0,M)$
+    pub fn from_bytes_be(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::from_u64_bytes(bytes, u64::from_be_bytes)
+    }
+
+    fn from_u64_bytes(bytes: &[u8], decode: fn([u8; 8]) -> u64) -> Result<Self, DecodeError> {
+        let bytes: [u8; 8] = bytes.try_into().map_err(|_| DecodeError::InvalidLength {
+            expected: 8,
+            found: bytes.len(),
+        })?;
+        let n = decode(bytes);
+        if n < M {
+            Ok(Self(n))
+        } else {
+            Err(DecodeError::NotCanonical)
+        }
+    }
+
+    /// Returns the multiplicative order, the smallest $k>0$ with $x^k=1$, or None for zero.
+    /// Starts from $M-1$ and divides by each of its prime factors while the power stays one
+    pub fn multiplicative_order(&self) -> Option<u64> {
+        if self.0 == 0 {
+            return None;
+        }
+        let mut order = M - 1;
+        let (mut n, mut q) = (M - 1, 2);
+        while n > 1 {
+            if q * q > n {
+                q = n;
+            }
+            if n.is_multiple_of(q) {
+                while n.is_multiple_of(q) {
+                    n /= q;
+                }
+                while order.is_multiple_of(q) && self.pow(order / q) == Self::one() {
+                    order /= q;
+                }
+            }
+            q += 1;
+        }
+        Some(order)
+    }
+
+    fn inv_extended_gcd(&self) -> Option<Self> {
+        let (gcd, c, _) = extended_gcd(self.0 as i64, M as i64);
+        if gcd == 1 {
+            if c < 0 {
+                Some(Self((M as i64 + c) as u64))
+            } else {
+                Some(Self(c as u64))
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// Decodes the little-endian canonical encoding, rejecting values not in $[0,M)$
+impl<const M: u64> TryFrom<&[u8]> for U64Field<M> {
+    type Error = DecodeError;
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes_le(bytes)
+    }
+}
+
 #[allow(non_snake_case)]
 impl<const M: u64> Field for U64Field<M> {
     type Order = u64;
+    const MODULUS: u64 = M;
 
     fn order() -> Self::Order {
         M
@@ -50,15 +206,10 @@ impl<const M: u64> Field for U64Field<M> {
         self.0 < M
     }
     fn inv(&self) -> Option<Self> {
-        let (gcd, c, _) = extended_gcd(self.0 as i64, M as i64);
-        if gcd == 1 {
-            if c < 0 {
-                Some(Self((M as i64 + c) as u64))
-            } else {
-                Some(Self(c as u64))
-            }
+        if M == 101 {
+            Some(Self(f101_inverses()[self.0 as usize])).filter(|inv| !inv.is_zero())
         } else {
-            None
+            self.inv_extended_gcd()
         }
     }
     fn pow(&self, mut exp: u64) -> Self {
@@ -73,6 +224,42 @@ impl<const M: u64> Field for U64Field<M> {
         }
         result
     }
+    // https://en.wikipedia.org/wiki/Tonelli%E2%80%93Shanks_algorithm
+    fn sqrt(&self) -> Option<Self> {
+        if self.is_zero() || M == 2 {
+            return Some(*self);
+        }
+        // euler criterion, check if it is a quadratic residue
+        if self.pow((M - 1) / 2) != Self::one() {
+            return None;
+        }
+        // M-1 = q*2^s with q odd
+        let (mut q, mut s) = (M - 1, 0);
+        while q % 2 == 0 {
+            q /= 2;
+            s += 1;
+        }
+        // find a quadratic non-residue
+        let mut z = Self::from(2u64);
+        while z.pow((M - 1) / 2) == Self::one() {
+            z += Self::one();
+        }
+        let (mut m, mut c, mut t, mut r) = (s, z.pow(q), self.pow(q), self.pow(q.div_ceil(2)));
+        while t != Self::one() {
+            // find the least i such t^(2^i) = 1
+            let (mut i, mut t_pow) = (0, t);
+            while t_pow != Self::one() {
+                t_pow = t_pow * t_pow;
+                i += 1;
+            }
+            let b = c.pow(1 << (m - i - 1));
+            m = i;
+            c = b * b;
+            t = t * c;
+            r = r * b;
+        }
+        Some(r)
+    }
     fn carrying_mul(&self, rhs: &Self, carry: &mut Self) -> Self {
         let r = self.0 as u128 * rhs.0 as u128 + carry.0 as u128;
 
@@ -85,7 +272,7 @@ impl<const M: u64> Field for U64Field<M> {
 impl<const M: u64> From<i64> for U64Field<M> {
     fn from(n: i64) -> Self {
         if n < 0 {
-            -Self::from(-n as u64)
+            -Self::from(n.unsigned_abs())
         } else {
             Self::from(n as u64)
         }
@@ -107,40 +294,40 @@ impl<const M: u64> Display for U64Field<M> {
 impl<const M: u64> Add for U64Field<M> {
     type Output = U64Field<M>;
     fn add(self, rhs: Self) -> Self::Output {
-        U64Field((self.0 + rhs.0) % M)
+        U64Field(U64Field::<M>::add_mod(self.0, rhs.0))
     }
 }
 
 impl<const M: u64> Add<&U64Field<M>> for U64Field<M> {
     type Output = U64Field<M>;
     fn add(self, rhs: &U64Field<M>) -> Self::Output {
-        U64Field((self.0 + rhs.0) % M)
+        U64Field(U64Field::<M>::add_mod(self.0, rhs.0))
     }
 }
 
 impl<const M: u64> Add<U64Field<M>> for &U64Field<M> {
     type Output = U64Field<M>;
     fn add(self, rhs: U64Field<M>) -> Self::Output {
-        U64Field((self.0 + rhs.0) % M)
+        U64Field(U64Field::<M>::add_mod(self.0, rhs.0))
     }
 }
 
 impl<const M: u64> Add<&U64Field<M>> for &U64Field<M> {
     type Output = U64Field<M>;
     fn add(self, rhs: &U64Field<M>) -> Self::Output {
-        U64Field((self.0 + rhs.0) % M)
+        U64Field(U64Field::<M>::add_mod(self.0, rhs.0))
     }
 }
 
 impl<const M: u64> AddAssign<&U64Field<M>> for U64Field<M> {
     fn add_assign(&mut self, rhs: &U64Field<M>) {
-        self.0 = (self.0 + rhs.0) % M;
+        self.0 = U64Field::<M>::add_mod(self.0, rhs.0);
     }
 }
 
 impl<const M: u64> AddAssign<U64Field<M>> for U64Field<M> {
     fn add_assign(&mut self, rhs: U64Field<M>) {
-        self.0 = (self.0 + rhs.0) % M;
+        self.0 = U64Field::<M>::add_mod(self.0, rhs.0);
     }
 }
 
@@ -174,28 +361,28 @@ impl<const M: u64> Neg for &U64Field<M> {
 impl<const M: u64> Mul for U64Field<M> {
     type Output = U64Field<M>;
     fn mul(self, rhs: Self) -> Self::Output {
-        U64Field((self.0 * rhs.0) % M)
+        U64Field(U64Field::<M>::mul_mod(self.0, rhs.0))
     }
 }
 
 impl<const M: u64> Mul<&U64Field<M>> for &U64Field<M> {
     type Output = U64Field<M>;
     fn mul(self, rhs: &U64Field<M>) -> Self::Output {
-        U64Field((self.0 * rhs.0) % M)
+        U64Field(U64Field::<M>::mul_mod(self.0, rhs.0))
     }
 }
 
 impl<const M: u64> Mul<&U64Field<M>> for U64Field<M> {
     type Output = U64Field<M>;
     fn mul(self, rhs: &U64Field<M>) -> Self::Output {
-        U64Field((self.0 * rhs.0) % M)
+        U64Field(U64Field::<M>::mul_mod(self.0, rhs.0))
     }
 }
 
 impl<const M: u64> Mul<U64Field<M>> for &U64Field<M> {
     type Output = U64Field<M>;
     fn mul(self, rhs: U64Field<M>) -> Self::Output {
-        U64Field((self.0 * rhs.0) % M)
+        U64Field(U64Field::<M>::mul_mod(self.0, rhs.0))
     }
 }
 
@@ -215,7 +402,7 @@ impl<const M: u64> Mul<Poly<U64Field<M>>> for U64Field<M> {
 
 impl<const M: u64> MulAssign<&U64Field<M>> for U64Field<M> {
     fn mul_assign(&mut self, rhs: &U64Field<M>) {
-        self.0 = (self.0 * rhs.0) % M;
+        self.0 = Self::mul_mod(self.0, rhs.0);
     }
 }
 
@@ -230,6 +417,7 @@ impl<const M: u64> Div for U64Field<M> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ec::DecodeError;
 
     fn f101(n: u64) -> U64Field<101> {
         U64Field::from(n)
@@ -252,4 +440,216 @@ mod tests {
         assert_eq!(f101(100) * f101(100), f101(100).pow(2));
         assert_eq!(f101(100) * f101(100) * f101(100), f101(100).pow(3));
     }
+    #[test]
+    fn test_normalize() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let hash = |f: U64Field<101>| {
+            let mut hasher = DefaultHasher::new();
+            f.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        assert_eq!(U64Field::<101>(201).normalize(), f101(100));
+        let values = [
+            f101(100),
+            f101(201),
+            f101(0) - f101(1),
+            -f101(1),
+            U64Field::<101>::from(-1i64),
+            f101(10) * f101(10),
+            (f101(1) / f101(100)).unwrap(),
+        ];
+        for v in values.iter() {
+            assert_eq!(*v, v.normalize());
+            assert!(v.in_field());
+            assert_eq!(hash(*v), hash(f101(100)));
+        }
+
+        // -i64::MIN does not fit in i64
+        assert_eq!(U64Field::<101>::from(i64::MIN), -f101(1 << 63));
+        assert_eq!(
+            U64Field::<101>::from(i64::MIN) + U64Field::<101>::from(i64::MAX),
+            -f101(1)
+        );
+    }
+    #[test]
+    fn test_f101_inverses() {
+        assert_eq!(None, f101(0).inv());
+        for n in 1..101 {
+            assert_eq!(f101(n).inv(), f101(n).inv_extended_gcd());
+            assert_eq!(f101(n) * f101(n).inv().unwrap(), f101(1));
+        }
+    }
+    #[test]
+    fn test_characteristic() {
+        assert_eq!(U64Field::<101>::characteristic(), 101);
+        assert_eq!(U64Field::<101>::MODULUS, 101);
+        assert_eq!(
+            U64Field::<3221225473>::characteristic(),
+            3 * 2u64.pow(30) + 1
+        );
+    }
+    #[test]
+    fn test_bytes_endianness() {
+        type F = U64Field<3221225473>;
+        let v = F::from(0x0102_0304u64);
+        assert_eq!(v.to_bytes_le(), [4, 3, 2, 1, 0, 0, 0, 0]);
+        assert_eq!(v.to_bytes_be(), [0, 0, 0, 0, 1, 2, 3, 4]);
+        assert_ne!(v.to_bytes_le(), v.to_bytes_be());
+        assert_eq!(F::from_bytes_le(&v.to_bytes_le()), Ok(v));
+        assert_eq!(F::from_bytes_be(&v.to_bytes_be()), Ok(v));
+        assert_eq!(
+            F::from_bytes_be(&v.to_bytes_le()),
+            Err(DecodeError::NotCanonical)
+        );
+        assert!(F::from_bytes_be(&v.to_bytes_be()[1..]).is_err());
+    }
+
+    #[test]
+    fn test_from_u128() {
+        type F = U64Field<3221225473>;
+        let n = u64::MAX as u128 * 3 + 5;
+        assert_eq!(F::from_u128(n), F::from((n % 3221225473) as u64));
+        assert_eq!(
+            F::from_u128(n),
+            F::from(u64::MAX) * F::from(3u64) + F::from(5u64)
+        );
+        assert_eq!(F::from_u128(3221225473), F::zero());
+
+        // sums and products near a 64-bit modulus don't overflow
+        type Big = U64Field<18446744073709551557>;
+        let a = Big::from(u64::MAX - 60);
+        assert_eq!(a + a, Big::from(u64::MAX - 62));
+        assert_eq!(a - (a + a), -a);
+        let mut b = a;
+        b += a;
+        assert_eq!(b, a + a);
+        assert_eq!(a * a, Big::from(4u64));
+        assert_eq!(
+            a * a,
+            Big::from_u128((u64::MAX - 60) as u128 * (u64::MAX - 60) as u128)
+        );
+    }
+
+    #[test]
+    fn test_cast() {
+        type Fr = U64Field<17>;
+        assert_eq!(Fr::from(5u64).cast::<101>(), Ok(f101(5)));
+        assert_eq!(f101(16).cast::<17>(), Ok(Fr::from(16u64)));
+        assert_eq!(
+            f101(100).cast::<17>(),
+            Err(FieldError::OutOfRange {
+                value: 100,
+                modulus: 17
+            })
+        );
+        assert!(f101(17).cast::<17>().is_err());
+    }
+
+    #[test]
+    fn test_bytes() {
+        for n in [0u64, 1, 2, 50, 100] {
+            let bytes = f101(n).to_bytes();
+            assert_eq!(U64Field::<101>::try_from(&bytes[..]), Ok(f101(n)));
+        }
+        assert_eq!(f101(2).to_bytes(), [2, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            U64Field::<101>::try_from(&[101u8, 0, 0, 0, 0, 0, 0, 0][..]),
+            Err(DecodeError::NotCanonical)
+        );
+        assert_eq!(
+            U64Field::<101>::try_from(&f101(2).to_bytes()[..7]),
+            Err(DecodeError::InvalidLength {
+                expected: 8,
+                found: 7
+            })
+        );
+    }
+
+    #[test]
+    fn test_multiplicative_order() {
+        assert_eq!(f101(0).multiplicative_order(), None);
+        assert_eq!(f101(1).multiplicative_order(), Some(1));
+        assert_eq!(f101(100).multiplicative_order(), Some(2));
+        assert_eq!(f101(2).multiplicative_order(), Some(100));
+        for n in 1..101 {
+            let order = f101(n).multiplicative_order().unwrap();
+            assert_eq!(f101(n).pow(order), f101(1));
+            assert!((1..order).all(|k| f101(n).pow(k) != f101(1)));
+        }
+
+        // the stark101 trace domain generator, 5^(3·2^20) in the 3·2^30+1 field
+        type F = U64Field<3221225473>;
+        let g = F::from(5u64).pow(3 * (1 << 20));
+        assert_eq!(g.multiplicative_order(), Some(1024));
+        assert_eq!(F::from(5u64).multiplicative_order(), Some(3221225472));
+    }
+
+    #[test]
+    fn test_from_strict() {
+        assert_eq!(U64Field::<101>::from_strict(100), f101(100));
+        assert_eq!(U64Field::<101>::from_strict(0), f101(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "value 101 >= modulus 101")]
+    fn test_from_strict_out_of_range() {
+        U64Field::<101>::from_strict(101);
+    }
+
+    #[test]
+    fn test_pow2() {
+        assert_eq!(U64Field::<101>::pow2(0), f101(1));
+        assert_eq!(U64Field::<101>::pow2(7), f101(128 % 101));
+
+        type F = U64Field<3221225473>;
+        assert_eq!(F::pow2(62), F::from(1u64 << 62));
+        assert_eq!(F::pow2(30) * F::from(3u64) + F::one(), F::zero());
+        assert_eq!(F::pow2(100), F::from(2u64).pow(100));
+    }
+
+    #[test]
+    fn test_inv_or_zero() {
+        assert_eq!(f101(0).inv_or_zero(), f101(0));
+        for n in 0..101 {
+            assert_eq!(f101(n).inv_or_zero(), f101(n).inv().unwrap_or(f101(0)));
+        }
+    }
+
+    #[test]
+    fn test_try_div() {
+        let (a, b) = (f101(6), f101(3));
+        assert_eq!(a.try_div(&b), Ok(f101(2)));
+        assert_eq!(a.try_div(&f101(0)), Err(FieldError::DivisionByZero));
+
+        let double_ratio = || -> Result<_, FieldError> { Ok(a.try_div(&b)? * f101(2)) };
+        assert_eq!(double_ratio(), Ok(f101(4)));
+    }
+
+    #[test]
+    fn test_pow_ct() {
+        type F101 = U64Field<101>;
+        for base in [0u64, 1, 2, 3, 50, 100].iter().map(|b| F101::from(*b)) {
+            for exp in 0..101 {
+                assert_eq!(base.pow_ct(exp), base.pow(exp));
+            }
+            assert_eq!(base.pow_ct(u64::MAX), base.pow(u64::MAX));
+        }
+    }
+
+    #[test]
+    fn test_sqrt() {
+        for n in 0..101 {
+            let sqrt = f101(n * n).sqrt().unwrap();
+            assert!(sqrt == f101(n) || sqrt == -f101(n));
+        }
+        // 2 is not a quadratic residue in F101
+        assert_eq!(None, f101(2).sqrt());
+
+        type F = U64Field<15485863>;
+        let n = F::from(1234567u64);
+        assert_eq!(n * n, (n * n).sqrt().unwrap().pow(2));
+    }
 }